@@ -21,13 +21,17 @@ fn main() {
 
     let mut mig = OpenOptions::new().write(true).create(true).truncate(true).open("migrations.sql").unwrap();
 
-    for up in ups {
+    for up in &ups {
         let mut file = File::open(up).unwrap();
         let mut sql = "".to_owned();
         file.read_to_string(&mut sql).unwrap();
         writeln!(mig, "{}", sql).unwrap();
     }
+    // Dictionaries built with fewer migrations can not be appended to
+    writeln!(mig, "PRAGMA user_version = {};", ups.len()).unwrap();
     writeln!(mig, "SELECT true").unwrap();
 
+    println!("cargo:rustc-env=EITARO_SCHEMA_VERSION={}", ups.len());
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=migrations");
 }
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE VIRTUAL TABLE definitions_fts USING fts5(term, text, content='definitions', content_rowid='id', tokenize='trigram');
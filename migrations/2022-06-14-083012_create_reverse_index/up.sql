@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE reverse_index (
+  id INTEGER PRIMARY KEY NOT NULL,
+  fragment TEXT NOT NULL,
+  term TEXT NOT NULL,
+  source TEXT DEFAULT NULL
+);
+CREATE INDEX reverse_index_fragment_index ON reverse_index(fragment);
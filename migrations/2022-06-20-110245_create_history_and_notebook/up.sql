@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- User data, these tables are kept through rebuilding
+CREATE TABLE IF NOT EXISTS history (
+  id INTEGER PRIMARY KEY NOT NULL,
+  term TEXT NOT NULL,
+  source TEXT NOT NULL,
+  found BOOLEAN NOT NULL,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS history_term_index ON history(term);
+CREATE TABLE IF NOT EXISTS notebook (
+  term TEXT NOT NULL PRIMARY KEY,
+  memo TEXT DEFAULT NULL,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
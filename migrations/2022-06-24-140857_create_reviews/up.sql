@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- User data, this table is kept through rebuilding
+CREATE TABLE IF NOT EXISTS reviews (
+  term TEXT NOT NULL PRIMARY KEY,
+  easiness REAL NOT NULL,
+  interval_days INTEGER NOT NULL,
+  repetitions INTEGER NOT NULL,
+  due INTEGER NOT NULL,
+  reviewed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS reviews_due_index ON reviews(due);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX definitions_source_index;
+ALTER TABLE aliases DROP COLUMN origin;
+ALTER TABLE lemmatizations DROP COLUMN origin;
+ALTER TABLE levels DROP COLUMN origin;
+ALTER TABLE tags DROP COLUMN origin;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- `source` is already used for the alias/lemmatization source word, so the dictionary source name is stored as `origin`
+ALTER TABLE aliases ADD COLUMN origin TEXT DEFAULT NULL;
+ALTER TABLE lemmatizations ADD COLUMN origin TEXT DEFAULT NULL;
+ALTER TABLE levels ADD COLUMN origin TEXT DEFAULT NULL;
+ALTER TABLE tags ADD COLUMN origin TEXT DEFAULT NULL;
+CREATE INDEX definitions_source_index ON definitions(source);
@@ -65,13 +65,13 @@ struct Word {
 const INDENT: &str = "    ";
 
 
-pub fn analyze<T: AsRef<Path>>(mut opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
+pub fn analyze<T: AsRef<Path>>(mut opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
 
     let mut text = "".to_owned();
     stdin().read_to_string(&mut text)?;
 
-    let common = analyze_common(&mut dic, &text)?;
+    let common = analyze_common(&dic, &text)?;
 
     {
         let mut opt_to_check = opt.clone();
@@ -88,7 +88,7 @@ pub fn analyze<T: AsRef<Path>>(mut opt: Opt, dictionary_path: &T) -> AppResultU
         analyze_svl(&common)?;
     }
     if let Some(n) = opt.usage.or_else(|| if opt.all { Some(20) } else { None }) {
-        analyze_usage(&mut dic, &common, n)?;
+        analyze_usage(&dic, &common, n)?;
     }
     if opt.in_svl || opt.all {
         let is_leveled = |lv| {
@@ -111,7 +111,7 @@ pub fn analyze<T: AsRef<Path>>(mut opt: Opt, dictionary_path: &T) -> AppResultU
 }
 
 
-fn analyze_common(dic: &mut Dictionary, text: &str) -> AppResult<Common> {
+fn analyze_common(dic: &Dictionary, text: &str) -> AppResult<Common> {
     let mut words = HashMap::<&str, usize>::new();
 
     let chars = str_utils::simple_words_pattern();
@@ -268,7 +268,7 @@ where F: Fn(Level) -> bool {
     Ok(())
 }
 
-fn analyze_usage(dictionary: &mut Dictionary, common: &Common, n: usize) -> AppResultU {
+fn analyze_usage(dictionary: &Dictionary, common: &Common, n: usize) -> AppResultU {
     println!("Usage ranking:");
     let mut words: Vec<(&str, usize)> = common.words.iter().map(|it| (it.word.as_ref(), it.count)).collect();
     words.sort_by(|(_, a), (_, b)| b.cmp(a));
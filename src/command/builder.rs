@@ -1,99 +1,294 @@
 
-use std::borrow::Cow;
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::thread::spawn;
 
 use regex::Regex;
 use separator::Separatable;
 use structopt::StructOpt;
 use shellexpand;
 
-use crate::dictionary::Dictionary;
-use crate::errors::{AppError, AppResult};
-use crate::loader::{csv, eijiro, ejdic, gene, json_simple_key_value, Loader};
-use crate::types::DictionaryFormat;
+use crate::dictionary::{Dictionary, WriteMode};
+use crate::errors::{AppError, AppResult, AppResultU};
+use crate::loader::{csv, eijiro, ejdic, gene, json_simple_key_value, load, Records};
+use crate::loader::encoding::{Encoding, Utf8Reader};
+use crate::loader::progress::ProgressReader;
+use crate::loader::report::Report;
+use crate::types::{DICTIONARY_FORMAT_NAMES, DictionaryFormat};
+
+
+
+const GENE_SIGNATURE: &str = " / This book describes Jpan and its kaisha at the cutting edge.";
+/// Ratio of the sample lines to accept the format
+const MIN_SCORE: f64 = 0.5;
+/// Parsed chunks waiting for the writer
+const QUEUE_SIZE: usize = 4;
+const SAMPLE_LINES: usize = 100;
+const SAMPLE_SIZE: usize = 64 * 1024;
 
 
 #[derive(StructOpt, Debug)]
 pub struct Opt {
-    /// Dictionary files
+    /// Keep the current entries and add the given files
+    #[structopt(short, long, conflicts_with = "replace_source")]
+    append: bool,
+    /// Show the detected formats and encodings without building
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+    /// Format of the files (Unless given by `FORMAT:` of each file)
+    #[structopt(short, long, possible_values = DICTIONARY_FORMAT_NAMES)]
+    format: Option<DictionaryFormat>,
+    /// Skip the malformed records instead of aborting
+    #[structopt(short, long)]
+    lenient: bool,
+    /// Write the problems found in the files
+    #[structopt(long)]
+    report: Option<PathBuf>,
+    /// Replace only the entries of the source (Unnamed files are loaded as this source)
+    #[structopt(short, long = "replace-source")]
+    replace_source: Option<String>,
+    /// Dictionary files ([FORMAT:][SOURCE@]PATH)
     files: Vec<PathBuf>,
 }
 
+struct Detected {
+    encoding: Encoding,
+    format: DictionaryFormat,
+    /// Ratio of the sample lines in the format (None if the format is given)
+    score: Option<f64>,
+}
+
+#[derive(Clone)]
+struct Input {
+    format: Option<DictionaryFormat>,
+    path: String,
+    source: Option<String>,
+}
+
+/// Sent from the parser thread to the writer
+enum Parsed {
+    Records(Records),
+    Source(Option<String>),
+}
+
 
 pub fn build_dictionary<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> Result<(), AppError> {
-    use DictionaryFormat::*;
+    let input_pattern = input_pattern()?;
 
-    let mut dictionary = Dictionary::new(dictionary_path);
-    let named_pattern = Regex::new(r"^(\w+)@(.+)$")?;
-
-    let stat = dictionary.write(move |writer| {
-        for file in &opt.files {
-            let (source, file) = extract_source_and_path(&file, &named_pattern)?;
-            println!("[{} ({})]", file, source.unwrap_or("-"));
-            let format = guess(&file.as_ref())?;
-            let mut file = File::open(file.as_ref())?;
-            let mut writer = writer.clone().with_source(source);
-            match format {
-                Csv => csv::CsvLoader::default().load(&mut file, &mut writer)?,
-                Eijiro => eijiro::EijiroLoader::default().load(&mut file, &mut writer)?,
-                Ejdic => ejdic::EjdicLoader::default().load(&mut file, &mut writer)?,
-                Gene => gene::GeneLoader::default().load(&mut file, &mut writer)?,
-                JsonSimpleKeyValue => json_simple_key_value::JsonSimpleKeyValueLoader::default().load(&mut file, &mut writer)?,
+    let mut inputs = vec![];
+    for file in &opt.files {
+        let mut input = parse_input(&file, &input_pattern)?;
+        input.format = input.format.or(opt.format);
+        input.source = input.source.or_else(|| opt.replace_source.clone());
+        inputs.push(input);
+    }
+
+    if opt.dry_run {
+        for input in &inputs {
+            let detected = detect(input)?;
+            let how = match detected.score {
+                Some(score) => format!("detected from {:.0}% of lines", score * 100.0),
+                None => "given".to_owned(),
             };
+            println!(
+                "{}: format={} ({}), encoding={}, source={}",
+                input.path,
+                detected.format.name(),
+                how,
+                detected.encoding.name(),
+                input.source.as_ref().map(String::as_str).unwrap_or("-"));
         }
+        return Ok(());
+    }
+
+    let mut dictionary = Dictionary::new(&[dictionary_path]);
+
+    let mode = if let Some(source) = opt.replace_source.as_ref() {
+        WriteMode::Replace(source)
+    } else if opt.append {
+        WriteMode::Append
+    } else {
+        WriteMode::Rebuild
+    };
+
+    let lenient = opt.lenient;
+    let mut report = None;
+
+    let stat = dictionary.write(mode, |writer| {
+        let (tx, rx) = sync_channel(QUEUE_SIZE);
+        let inputs = inputs.clone();
+        let parser = spawn(move || {
+            let mut report = Report::new(lenient);
+            match parse_files(&inputs, &mut report, &tx) {
+                Ok(()) => Some(report),
+                Err(error) => {
+                    let _ = tx.send(Err(error));
+                    None
+                },
+            }
+        });
+
+        // Only this thread writes to the database
+        for parsed in rx {
+            match parsed? {
+                Parsed::Source(source) => writer.set_source(source.as_ref().map(String::as_str)),
+                Parsed::Records(records) => records.write(writer)?,
+            }
+        }
+        report = parser.join().map_err(|_| AppError::Unexpect("Parser thread panicked"))?;
+
         println!("[Finalize]");
         Ok(())
     })?;
 
     println!("Finished: {} words, {} aliases", stat.words.separated_string(), stat.aliases.separated_string());
 
+    if let Some(report) = report {
+        if !report.is_empty() {
+            println!("{}", report.summary());
+        }
+        if let Some(path) = opt.report.as_ref() {
+            report.write(&mut File::create(path)?)?;
+        }
+    }
+
     Ok(())
 }
 
-fn extract_source_and_path<'a, T: AsRef<Path>>(file: &'a T, pattern: &Regex) -> AppResult<(Option<&'a str>, Cow<'a, str>)> {
-    let file = file.as_ref().to_str().ok_or(AppError::Unexpect("Invalid string"))?;
-    if let Some(caps) = pattern.captures(file) {
-        if caps.len() == 3 {
-            Ok((
-                Some(caps.get(1).unwrap().as_str()),
-                shellexpand::tilde(caps.get(2).unwrap().as_str())
-            ))
-        } else {
-            Ok((None, Cow::Borrowed(file)))
-        }
-    } else {
-        Ok((None, Cow::Borrowed(file)))
+/// Parse the files in order (Lines are parsed in parallel), and send the records to the writer
+fn parse_files(inputs: &[Input], report: &mut Report, tx: &SyncSender<AppResult<Parsed>>) -> AppResultU {
+    use DictionaryFormat::*;
+
+    let send = |parsed| tx.send(Ok(parsed)).map_err(|_| AppError::Unexpect("Writer has stopped"));
+
+    for input in inputs {
+        let detected = detect(input)?;
+        println!(
+            "[{} ({})] {}, {}",
+            input.path,
+            input.source.as_ref().map(String::as_str).unwrap_or("-"),
+            detected.format.name(),
+            detected.encoding.name());
+        let file = File::open(&input.path)?;
+        let size = file.metadata()?.len();
+        let mut file = ProgressReader::new(BufReader::new(file), size);
+        let mut source = Utf8Reader::new(&mut file, detected.encoding)?;
+        send(Parsed::Source(input.source.clone()))?;
+        report.set_file(&input.path);
+        let f = |records| send(Parsed::Records(records));
+        match detected.format {
+            Csv => load(&csv::CsvLoader::default(), &mut source, report, f)?,
+            Eijiro => load(&eijiro::EijiroLoader::default(), &mut source, report, f)?,
+            Ejdic => load(&ejdic::EjdicLoader::default(), &mut source, report, f)?,
+            Gene => load(&gene::GeneLoader::default(), &mut source, report, f)?,
+            JsonSimpleKeyValue => load(&json_simple_key_value::JsonSimpleKeyValueLoader::default(), &mut source, report, f)?,
+            Tsv => load(&csv::CsvLoader::tsv(), &mut source, report, f)?,
+        };
+        file.finish();
     }
+
+    Ok(())
 }
 
-fn guess<T: AsRef<Path>>(source_path: &T) -> Result<DictionaryFormat, AppError> {
-    let mut file = File::open(source_path)?;
-    let mut head = [0u8;100];
-    let size = file.read(&mut head)?;
-    let head = &head[0..size];
+fn detect(input: &Input) -> AppResult<Detected> {
+    let mut head = vec![];
+    File::open(&input.path)?.take(SAMPLE_SIZE as u64).read_to_end(&mut head)?;
+    let encoding = Encoding::detect(&head);
 
-    if head.starts_with(b" / This book describes Jpan and its kaisha at the cutting edge.") {
-        return Ok(DictionaryFormat::Gene)
+    if let Some(format) = input.format {
+        return Ok(Detected { encoding, format, score: None });
     }
 
-    // 81a1 == ■
-    if head.starts_with(b"\x81\xa1") {
-        return Ok(DictionaryFormat::Eijiro);
+    let mut sample = encoding.decode(&head);
+    if head.len() == SAMPLE_SIZE {
+        // Drop the last line that may be cut off
+        if let Some(last) = sample.rfind('\n') {
+            sample.truncate(last);
+        }
     }
 
-    if head.starts_with(b"{\"") {
-        return Ok(DictionaryFormat::JsonSimpleKeyValue)
+    let (format, score) = guess(sample.trim_start_matches('\u{feff}')).ok_or(AppError::Eitaro("Unknown format"))?;
+    Ok(Detected { encoding, format, score: Some(score) })
+}
+
+/// The format and the ratio of the lines in the format
+fn guess(sample: &str) -> Option<(DictionaryFormat, f64)> {
+    use DictionaryFormat::*;
+
+    if sample.starts_with(GENE_SIGNATURE) {
+        return Some((Gene, 1.0));
     }
 
-    if head.contains(&b'\t') {
-        return Ok(DictionaryFormat::Ejdic);
+    if sample.trim_start().starts_with('{') {
+        return Some((JsonSimpleKeyValue, 1.0));
     }
 
-    if head.contains(&b',') {
-        return Ok(DictionaryFormat::Csv)
+    let lines: Vec<&str> = sample.lines().filter(|it| !it.trim().is_empty()).take(SAMPLE_LINES).collect();
+    if lines.is_empty() {
+        return None;
     }
 
-    Err(AppError::Eitaro("Unknown format"))
+    // In priority order
+    let candidates: [(DictionaryFormat, fn(&str) -> bool); 4] = [
+        (Eijiro, |line| line.starts_with('■') && line.contains(" : ")),
+        (Ejdic, |line| line.matches('\t').count() == 1),
+        (Tsv, |line| 2 <= line.matches('\t').count()),
+        (Csv, |line| line.contains(',') && !line.contains('\t')),
+    ];
+
+    let mut result = None;
+    for (format, matches) in candidates.iter() {
+        let score = lines.iter().filter(|line| matches(line)).count() as f64 / lines.len() as f64;
+        if MIN_SCORE <= score && result.map_or(true, |(_, best)| best < score) {
+            result = Some((*format, score));
+        }
+    }
+    result
+}
+
+/// `[FORMAT:][SOURCE@]PATH`
+/// `[format:][source@]path` (The prefix other than the format names is a part of the path. e.g. `C:\dict.txt`)
+fn input_pattern() -> AppResult<Regex> {
+    let pattern = format!(r"^(?:({}):)?(?:(\w+)@)?(.+)$", DICTIONARY_FORMAT_NAMES.join("|"));
+    Ok(Regex::new(&pattern)?)
+}
+
+fn parse_input<T: AsRef<Path>>(file: &T, pattern: &Regex) -> AppResult<Input> {
+    let file = file.as_ref().to_str().ok_or(AppError::Unexpect("Invalid string"))?;
+    let caps = pattern.captures(file).ok_or(AppError::Eitaro("Empty file name"))?;
+    Ok(Input {
+        format: caps.get(1).map(|it| it.as_str().parse()).transpose()?,
+        path: shellexpand::tilde(caps.get(3).unwrap().as_str()).into_owned(),
+        source: caps.get(2).map(|it| it.as_str().to_owned()),
+    })
+}
+
+
+#[cfg(test)]#[test]
+fn test_guess() {
+    use DictionaryFormat::*;
+
+    assert_eq!(guess("■cat : 猫\n■dog : 犬\n"), Some((Eijiro, 1.0)));
+    assert_eq!(guess("cat\t猫\ndog,hound\t犬 / 猟犬\n"), Some((Ejdic, 1.0)));
+    assert_eq!(guess("term\tdefinition\taliases\ncat\t猫\tkitty\ndog\t犬\t\n"), Some((Tsv, 1.0)));
+    assert_eq!(guess("term,definition\ncat,猫\ndog,犬\nthe end\n"), Some((Csv, 0.75)));
+    assert_eq!(guess("  {\"cat\": \"猫\"}"), Some((JsonSimpleKeyValue, 1.0)));
+    assert_eq!(guess("cat\ndog\n"), None);
+}
+
+#[cfg(test)]#[test]
+fn test_parse_input() {
+    let pattern = input_pattern().unwrap();
+    let parse = |file: &str| {
+        let input = parse_input(&file, &pattern).unwrap();
+        (input.format, input.source, input.path)
+    };
+
+    assert_eq!(parse("tsv:team@glossary.txt"), (Some(DictionaryFormat::Tsv), Some("team".to_owned()), "glossary.txt".to_owned()));
+    assert_eq!(parse("team@glossary.txt"), (None, Some("team".to_owned()), "glossary.txt".to_owned()));
+    assert_eq!(parse(r"C:\dict\EIJIRO.TXT"), (None, None, r"C:\dict\EIJIRO.TXT".to_owned()));
+    assert_eq!(parse("notes:v2.csv"), (None, None, "notes:v2.csv".to_owned()));
+    assert_eq!(parse("team:glossary.txt"), (None, None, "team:glossary.txt".to_owned()));
 }
@@ -0,0 +1,196 @@
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use deco::{dprint, dprintln};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use rustyline;
+use structopt::StructOpt;
+
+use crate::dictionary::{Dictionary, Entry, Text};
+use crate::errors::{AppResult, AppResultU};
+use crate::review::{MAX_GRADE, Schedule, today};
+
+
+
+#[derive(Debug, StructOpt)]
+pub struct Opt {
+    /// Maximum number of new cards
+    #[structopt(short, long, default_value = "20")]
+    n: usize,
+    /// Draw new cards from starred words (notebook)
+    #[structopt(short = "s", long)]
+    starred: bool,
+    /// Draw new cards from looked up words (history)
+    #[structopt(short = "l", long = "looked-up")]
+    looked_up: bool,
+    /// Draw new cards from SVL: minimum level (1 to 12)
+    #[structopt(long = "min")]
+    min: Option<u8>,
+    /// Draw new cards from SVL: maximum level
+    #[structopt(long = "max")]
+    max: Option<u8>,
+}
+
+enum Answer {
+    Grade(u8),
+    Quit,
+}
+
+
+pub fn drill<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
+    let today = today();
+
+    let due = dic.due_reviews(today)?;
+    let new = new_cards(&dic, &opt)?;
+
+    if due.is_empty() && new.is_empty() {
+        println!("No cards to review 🎉");
+        return Ok(());
+    }
+
+    println!("{} cards to review, {} new cards", due.len(), new.len());
+
+    let config = rustyline::config::Builder::new().build();
+    let mut editor = rustyline::Editor::<()>::with_config(config);
+
+    // Skip the cards not in the dictionary (e.g. removed by rebuilding) before counting
+    let mut cards = vec![];
+    for term in due.into_iter().chain(new.into_iter()) {
+        if let Some(entries) = dic.get(&term)? {
+            cards.push((term, entries));
+        }
+    }
+    let total = cards.len();
+    let mut reviewed = 0;
+
+    for (index, (term, entries)) in cards.iter().enumerate() {
+        println!();
+        dprint!([bold "[{}/{}] " !] index + 1, total);
+        show_front(entries);
+
+        if editor.readline("Press Enter to show the answer (q to quit) ").map(|it| it.trim() == "q").unwrap_or(true) {
+            break;
+        }
+
+        show_back(entries);
+
+        match ask_grade(&mut editor) {
+            Answer::Grade(grade) => {
+                let schedule = dic.get_schedule(term)?.unwrap_or_else(|| Schedule::new(today));
+                let schedule = schedule.review(grade, today);
+                dic.save_schedule(term, &schedule)?;
+                reviewed += 1;
+                dprintln!([cyan "Next review in {} day(s)" !] schedule.interval);
+            },
+            Answer::Quit => break,
+        }
+    }
+
+    println!();
+    println!("Reviewed {} card(s)", reviewed);
+
+    Ok(())
+}
+
+
+fn new_cards(dic: &Dictionary, opt: &Opt) -> AppResult<Vec<String>> {
+    let use_level = opt.min.is_some() || opt.max.is_some();
+    let (starred, looked_up) = if !opt.starred && !opt.looked_up && !use_level {
+        (true, true)
+    } else {
+        (opt.starred, opt.looked_up)
+    };
+
+    let mut known: HashSet<String> = dic.reviewed_terms()?.into_iter().collect();
+    let mut result = vec![];
+
+    let mut add = |term: String, result: &mut Vec<String>| {
+        if result.len() < opt.n && known.insert(term.clone()) {
+            result.push(term);
+        }
+    };
+
+    if starred {
+        for item in dic.notebook()? {
+            add(item.term, &mut result);
+        }
+    }
+
+    if looked_up {
+        for item in dic.history(None)? {
+            if item.found {
+                add(item.term, &mut result);
+            }
+        }
+    }
+
+    if use_level {
+        let mut words = dic.level_words(opt.min.unwrap_or(1), opt.max.unwrap_or(12))?;
+        words.shuffle(&mut thread_rng());
+        for word in words {
+            add(word, &mut result);
+        }
+    }
+
+    Ok(result)
+}
+
+fn show_front(entries: &[Entry]) {
+    let mut classes = vec![];
+    for entry in entries {
+        for definition in &entry.definitions {
+            for text in &definition.content {
+                if let Text::Class(class) = text {
+                    if !classes.contains(&class) {
+                        classes.push(class);
+                    }
+                }
+            }
+        }
+    }
+
+    dprint!([black on_yellow bold "{}" !] entries[0].key);
+    for class in classes {
+        dprint!([blue " {}" !] class);
+    }
+    println!();
+}
+
+fn show_back(entries: &[Entry]) {
+    for entry in entries {
+        for definition in &entry.definitions {
+            let texts: Vec<&str> = definition.content.iter().filter_map(|it| {
+                if let Text::Definition(s) = it {
+                    Some(s.as_str())
+                } else {
+                    None
+                }
+            }).collect();
+            if !texts.is_empty() {
+                dprintln!(["  " white bold "{}" !] texts.join(" "));
+            }
+        }
+    }
+}
+
+fn ask_grade(editor: &mut rustyline::Editor<()>) -> Answer {
+    loop {
+        let prompt = format!("Grade 0 (forgot) to {} (perfect), q to quit: ", MAX_GRADE);
+        match editor.readline(&prompt) {
+            Ok(ref input) => {
+                let input = input.trim();
+                if input == "q" {
+                    return Answer::Quit;
+                }
+                match input.parse::<u8>() {
+                    Ok(grade) if grade <= MAX_GRADE => return Answer::Grade(grade),
+                    _ => println!("Invalid input!"),
+                }
+            },
+            Err(_) => return Answer::Quit,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+
+use std::collections::HashMap;
+use std::io::{BufWriter, stdout, Write};
+use std::path::Path;
+
+use serde_derive::Serialize;
+use structopt::StructOpt;
+
+use crate::dictionary::{Dictionary, Entry};
+use crate::errors::{AppError, AppResult, AppResultU};
+use crate::loader::csv::CSV_HEADER;
+use crate::parser::{eijiro, ejdic};
+
+
+
+#[derive(Debug, StructOpt)]
+pub struct Opt {
+    /// Output format (Loadable by `eitaro build`)
+    #[structopt(short, long, default_value = "csv", possible_values = &["csv", "ejdic", "json"])]
+    format: String,
+}
+
+/// Aliases, inflections and levels by the term
+#[derive(Default)]
+struct Extra {
+    aliases: HashMap<String, Vec<String>>,
+    inflections: HashMap<String, Vec<String>>,
+    levels: HashMap<String, u8>,
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    definitions: Vec<String>,
+    aliases: &'a [String],
+    inflections: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    level: Option<u8>,
+}
+
+
+pub fn dump<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
+    let entries = dic.entries()?;
+    let extra = Extra::load(&dic)?;
+
+    let out = stdout();
+    let out = out.lock();
+    let mut out = BufWriter::new(out);
+
+    match &*opt.format {
+        "csv" => dump_csv(&mut out, &entries, &extra)?,
+        "ejdic" => dump_ejdic(&mut out, &entries, &extra)?,
+        "json" => dump_json(&mut out, &entries, &extra)?,
+        _ => return Err(AppError::Eitaro("Unknown dump format")),
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+
+impl Extra {
+    fn load(dic: &Dictionary) -> AppResult<Self> {
+        let mut result = Extra::default();
+
+        for (inflected, lemma) in dic.inflections()? {
+            result.inflections.entry(lemma).or_insert_with(Vec::new).push(inflected);
+        }
+
+        // Inflections are also registered as aliases
+        for (alias, target) in dic.aliases()? {
+            let is_inflection = result.inflections.get(&target).map(|it| it.contains(&alias)).unwrap_or(false);
+            if !is_inflection {
+                result.aliases.entry(target).or_insert_with(Vec::new).push(alias);
+            }
+        }
+
+        for (term, level) in dic.levels()? {
+            result.levels.insert(term.to_lowercase(), level);
+        }
+
+        Ok(result)
+    }
+
+    fn aliases(&self, term: &str) -> &[String] {
+        self.aliases.get(term).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn inflections(&self, term: &str) -> &[String] {
+        self.inflections.get(term).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn level(&self, term: &str) -> Option<u8> {
+        self.levels.get(term).cloned()
+    }
+}
+
+
+fn dump_csv<W: Write>(out: &mut W, entries: &[Entry], extra: &Extra) -> AppResultU {
+    let mut out = csv::Writer::from_writer(out);
+    out.write_record(CSV_HEADER)?;
+
+    for entry in entries {
+        for (index, definition) in entry.definitions.iter().enumerate() {
+            let definition_text = eijiro::unparse(&definition.content);
+            if index == 0 {
+                let level = extra.level(&entry.key).map(|it| it.to_string()).unwrap_or_default();
+                out.write_record(&[
+                    definition.key.as_str(),
+                    definition_text.as_str(),
+                    extra.aliases(&entry.key).join(",").as_str(),
+                    extra.inflections(&entry.key).join(",").as_str(),
+                    level.as_str()])?;
+            } else {
+                out.write_record(&[definition.key.as_str(), definition_text.as_str(), "", "", ""])?;
+            }
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn dump_ejdic<W: Write>(out: &mut W, entries: &[Entry], extra: &Extra) -> AppResultU {
+    for entry in entries {
+        let key = headword(entry);
+        let definitions: Vec<String> = entry.definitions.iter().map(|it| ejdic::unparse(&it.content)).collect();
+
+        let mut keys = vec![key];
+        keys.extend(extra.aliases(&entry.key).iter().map(String::as_str));
+        keys.extend(extra.inflections(&entry.key).iter().map(String::as_str));
+
+        writeln!(out, "{}\t{}", keys.join(","), definitions.join(" / "))?;
+    }
+
+    Ok(())
+}
+
+fn dump_json<W: Write>(out: &mut W, entries: &[Entry], extra: &Extra) -> AppResultU {
+    let mut result = serde_json::Map::new();
+
+    for entry in entries {
+        let json_entry = JsonEntry {
+            definitions: entry.definitions.iter().map(|it| eijiro::unparse(&it.content)).collect(),
+            aliases: extra.aliases(&entry.key),
+            inflections: extra.inflections(&entry.key),
+            level: extra.level(&entry.key),
+        };
+        result.insert(headword(entry).to_owned(), serde_json::to_value(json_entry)?);
+    }
+
+    serde_json::to_writer(&mut *out, &result)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Term in the original case
+fn headword(entry: &Entry) -> &str {
+    entry.definitions.first().map(|it| it.key.as_str()).unwrap_or(&entry.key)
+}
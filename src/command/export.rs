@@ -2,13 +2,19 @@ use std::collections::HashSet;
 use std::io::{BufRead, BufReader, BufWriter, Read, stdin, stdout, Write};
 use std::path::Path;
 
+use if_let_return::if_let_some;
 use regex::Regex;
 use structopt::StructOpt;
 
+pub mod anki;
 pub mod csv;
+pub mod html;
+pub mod json;
+pub mod markdown;
+pub mod tsv;
 
-use crate::dictionary::Dictionary;
-use crate::errors::{AppResult, AppResultU};
+use crate::dictionary::{Dictionary, Entry, Text};
+use crate::errors::{AppError, AppResult, AppResultU};
 use crate::str_utils;
 
 
@@ -18,34 +24,61 @@ pub struct Opt {
     /// Extract words from input as text
     #[structopt(short = "t", long = "as-text")]
     as_text: bool,
+    /// Output format
+    #[structopt(short, long, default_value = "csv", possible_values = &["anki", "csv", "html", "json", "markdown", "tsv"])]
+    format: String,
+    /// Deck name (for anki)
+    #[structopt(long, default_value = "eitaro")]
+    deck: String,
+    #[structopt(flatten)]
+    options: Options,
 }
 
-trait Exporter {
-    fn export<T: Write>(&self, dictionary: &mut Dictionary, words: &[&str], out: &mut T) -> AppResultU;
+#[derive(Clone, Debug, StructOpt)]
+pub struct Options {
+    /// Exclude examples
+    #[structopt(long = "no-examples", parse(from_flag = std::ops::Not::not))]
+    examples: bool,
+    /// Take only n definitions per word
+    #[structopt(short, long = "max-definitions")]
+    max_definitions: Option<usize>,
 }
 
+trait Exporter {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU;
+}
 
-pub fn export<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dictionary = Dictionary::new(dictionary_path);
-    let exporter = csv::CsvExporter();
 
-    let out = stdout();
-    let out = out.lock();
-    let mut out = BufWriter::new(out);
+pub fn export<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dictionary = Dictionary::new(dictionary_paths);
 
     let input = stdin();
     let input = input.lock();
     let mut reader = BufReader::new(input);
-    if opt.as_text {
+    let words = if opt.as_text {
         let mut buffer = "".to_owned();
         reader.read_to_string(&mut buffer)?;
-        let words = extract_text(&mut dictionary, &buffer)?;
-        let words = words.iter().map(String::as_ref).collect::<Vec<&str>>();
-        exporter.export(&mut dictionary, &words, &mut out)?;
+        extract_text(&dictionary, &buffer)?
     } else {
         let words = reader.lines().collect::<Result<Vec<String>, _>>()?;
-        let words: Vec<&str> = words.iter().map(String::as_ref).map(str::trim).collect();
-        exporter.export(&mut dictionary, &words, &mut out)?;
+        words.iter().map(|it| it.trim().to_owned()).collect()
+    };
+    let words = words.iter().map(String::as_ref).collect::<Vec<&str>>();
+
+    let out = stdout();
+    let out = out.lock();
+    let mut out = BufWriter::new(out);
+
+    let options = opt.options;
+
+    match &*opt.format {
+        "anki" => anki::AnkiExporter { deck: opt.deck, options }.export(&dictionary, &words, &mut out)?,
+        "csv" => csv::CsvExporter { options }.export(&dictionary, &words, &mut out)?,
+        "html" => html::HtmlExporter { options }.export(&dictionary, &words, &mut out)?,
+        "json" => json::JsonExporter { options }.export(&dictionary, &words, &mut out)?,
+        "markdown" => markdown::MarkdownExporter { options }.export(&dictionary, &words, &mut out)?,
+        "tsv" => tsv::TsvExporter { options }.export(&dictionary, &words, &mut out)?,
+        _ => return Err(AppError::Eitaro("Unknown export format")),
     }
 
     out.flush()?;
@@ -53,7 +86,39 @@ pub fn export<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
     Ok(())
 }
 
-fn extract_text(dictionary: &mut Dictionary, s: &str) -> AppResult<Vec<String>> {
+/// Lookup the word and apply the options
+fn lookup(dictionary: &Dictionary, word: &str, options: &Options) -> AppResult<Option<Vec<Entry>>> {
+    if_let_some!(entries = dictionary.get(word)?, Ok(None));
+
+    let mut rest = options.max_definitions.unwrap_or(usize::max_value());
+    let mut result = vec![];
+
+    for mut entry in entries {
+        if rest == 0 {
+            break;
+        }
+        if !options.examples {
+            for definition in &mut entry.definitions {
+                definition.content.retain(|it| if let Text::Example(_) = it { false } else { true });
+            }
+        }
+        entry.definitions.retain(|it| !it.content.is_empty());
+        entry.definitions.truncate(rest);
+        rest -= entry.definitions.len();
+        if !entry.definitions.is_empty() {
+            result.push(entry);
+        }
+    }
+
+    if result.is_empty() {
+        eprintln!("Definition not found: {}", word);
+        return Ok(None);
+    }
+
+    Ok(Some(result))
+}
+
+fn extract_text(dictionary: &Dictionary, s: &str) -> AppResult<Vec<String>> {
     let valid = Regex::new(r"\A[a-zA-Z]{2,}\z").unwrap();
 
     let mut words = HashSet::new();
@@ -0,0 +1,304 @@
+
+// Anki package (.apkg)
+// ref: https://github.com/ankidroid/Anki-Android/wiki/Database-Structure
+
+use std::env::temp_dir;
+use std::fs::{File, remove_file};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use askama_escape::{escape, Html};
+use diesel::connection::{Connection, SimpleConnection};
+use diesel::sql_types::{BigInt, Text as SqlText};
+use diesel::sqlite::SqliteConnection;
+use diesel::RunQueryDsl;
+use serde_json::json;
+use sha1::Sha1;
+use zip::ZipWriter;
+use zip::write::FileOptions;
+
+use crate::dictionary::{Dictionary, Entry, Text};
+use crate::errors::{AppError, AppResult, AppResultU};
+use super::{Exporter, lookup, Options};
+
+
+
+const FIELD_SEPARATOR: &str = "\x1f";
+
+const SCHEMA: &str = r#"
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null, scm integer not null, ver integer not null,
+    dty integer not null, usn integer not null, ls integer not null,
+    conf text not null, models text not null, decks text not null, dconf text not null, tags text not null);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null, mod integer not null, usn integer not null,
+    tags text not null, flds text not null, sfld integer not null, csum integer not null, flags integer not null, data text not null);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null, ord integer not null, mod integer not null,
+    usn integer not null, type integer not null, queue integer not null, due integer not null, ivl integer not null,
+    factor integer not null, reps integer not null, lapses integer not null, left integer not null,
+    odue integer not null, odid integer not null, flags integer not null, data text not null);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null, ivl integer not null, lastIvl integer not null,
+    factor integer not null, time integer not null, type integer not null);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+"#;
+
+const CSS: &str = ".card { font-family: sans-serif; font-size: 20px; text-align: center; }
+.eitaro-key { font-size: 32px; font-weight: bold; }
+.eitaro-class { color: #2a6bd1; }
+.eitaro-definitions { text-align: left; }
+.eitaro-example { color: #3a8a3a; font-size: 16px; }
+.eitaro-level { color: #888888; font-size: 14px; }";
+
+
+pub struct AnkiExporter {
+    pub deck: String,
+    pub options: Options,
+}
+
+struct Note {
+    front: String,
+    back: String,
+    sort_field: String,
+}
+
+
+impl Exporter for AnkiExporter {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU {
+        let mut notes = vec![];
+
+        for word in words {
+            if let Some(entries) = lookup(dictionary, word, &self.options)? {
+                let level = dictionary.get_level(&word)?;
+                notes.push(make_note(word, &entries, level));
+            }
+        }
+
+        let collection = make_collection(&self.deck, &notes)?;
+
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        zip.start_file("collection.anki2", FileOptions::default())?;
+        zip.write_all(&collection)?;
+        zip.start_file("media", FileOptions::default())?;
+        zip.write_all(b"{}")?;
+        let apkg = zip.finish()?;
+
+        out.write_all(apkg.get_ref())?;
+        Ok(())
+    }
+}
+
+
+fn make_collection(deck: &str, notes: &[Note]) -> AppResult<Vec<u8>> {
+    let path = temp_dir().join(format!("eitaro-{}.anki2", process::id()));
+    let result = write_collection(&path, deck, notes).and_then(|_| {
+        let mut buffer = vec![];
+        File::open(&path)?.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    });
+    let _ = remove_file(&path);
+    result
+}
+
+fn write_collection(path: &Path, deck: &str, notes: &[Note]) -> AppResultU {
+    let _ = remove_file(path);
+
+    let connection = SqliteConnection::establish(path.to_str().ok_or(AppError::Unexpect("WTF: temp path"))?)?;
+    connection.batch_execute(SCHEMA)?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let (now_sec, now_ms) = (now.as_secs() as i64, now.as_millis() as i64);
+    let model_id = now_ms;
+    let deck_id = now_ms + 1;
+
+    let models = json!({
+        model_id.to_string(): {
+            "css": CSS,
+            "did": deck_id,
+            "flds": [field("Front", 0), field("Back", 1)],
+            "id": model_id,
+            "latexPost": "\\end{document}",
+            "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+            "mod": now_sec,
+            "name": "Eitaro",
+            "req": [[0, "all", [0]]],
+            "sortf": 0,
+            "tags": [],
+            "tmpls": [{
+                "afmt": "{{FrontSide}}\n\n<hr id=answer>\n\n{{Back}}",
+                "bafmt": "",
+                "bqfmt": "",
+                "did": null,
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Front}}",
+            }],
+            "type": 0,
+            "usn": -1,
+            "vers": [],
+        }
+    });
+
+    let decks = json!({
+        "1": deck_json(1, "Default", now_sec),
+        deck_id.to_string(): deck_json(deck_id, deck, now_sec),
+    });
+
+    let dconf = json!({
+        "1": {
+            "autoplay": true,
+            "id": 1,
+            "lapse": {"delays": [10], "leechAction": 0, "leechFails": 8, "minInt": 1, "mult": 0},
+            "maxTaken": 60,
+            "mod": 0,
+            "name": "Default",
+            "new": {"bury": true, "delays": [1, 10], "initialFactor": 2500, "ints": [1, 4, 7], "order": 1, "perDay": 20, "separate": true},
+            "replayq": true,
+            "rev": {"bury": true, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1, "maxIvl": 36500, "minSpace": 1, "perDay": 100},
+            "timer": 0,
+            "usn": 0,
+        }
+    });
+
+    let conf = json!({
+        "activeDecks": [1],
+        "addToCur": true,
+        "collapseTime": 1200,
+        "curDeck": deck_id,
+        "curModel": model_id.to_string(),
+        "dueCounts": true,
+        "estTimes": true,
+        "newSpread": 0,
+        "nextPos": notes.len() + 1,
+        "sortBackwards": false,
+        "sortType": "noteFld",
+        "timeLim": 0,
+    });
+
+    connection.transaction::<_, AppError, _>(|| {
+        diesel::sql_query(
+            "INSERT INTO col VALUES (1, ?, ?, ?, 11, 0, 0, 0, ?, ?, ?, ?, '{}')")
+            .bind::<BigInt, _>(now_sec)
+            .bind::<BigInt, _>(now_ms)
+            .bind::<BigInt, _>(now_ms)
+            .bind::<SqlText, _>(conf.to_string())
+            .bind::<SqlText, _>(models.to_string())
+            .bind::<SqlText, _>(decks.to_string())
+            .bind::<SqlText, _>(dconf.to_string())
+            .execute(&connection)?;
+
+        for (index, note) in notes.iter().enumerate() {
+            let note_id = now_ms + index as i64;
+            let checksum = Sha1::from(&note.sort_field).digest().bytes();
+            let checksum = checksum[0..4].iter().fold(0i64, |acc, it| (acc << 8) | i64::from(*it));
+            // Stable GUID to update the notes on re-import
+            let guid = Sha1::from(format!("eitaro:{}:{}", deck, note.sort_field)).digest().to_string();
+
+            diesel::sql_query(
+                "INSERT INTO notes VALUES (?, ?, ?, ?, -1, '', ?, ?, ?, 0, '')")
+                .bind::<BigInt, _>(note_id)
+                .bind::<SqlText, _>(&guid[0..10])
+                .bind::<BigInt, _>(model_id)
+                .bind::<BigInt, _>(now_sec)
+                .bind::<SqlText, _>(format!("{}{}{}", note.front, FIELD_SEPARATOR, note.back))
+                .bind::<SqlText, _>(&note.sort_field)
+                .bind::<BigInt, _>(checksum)
+                .execute(&connection)?;
+
+            diesel::sql_query(
+                "INSERT INTO cards VALUES (?, ?, ?, 0, ?, -1, 0, 0, ?, 0, 0, 0, 0, 0, 0, 0, 0, '')")
+                .bind::<BigInt, _>(note_id)
+                .bind::<BigInt, _>(note_id)
+                .bind::<BigInt, _>(deck_id)
+                .bind::<BigInt, _>(now_sec)
+                .bind::<BigInt, _>(index as i64 + 1)
+                .execute(&connection)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn deck_json(id: i64, name: &str, now_sec: i64) -> serde_json::Value {
+    json!({
+        "collapsed": false,
+        "conf": 1,
+        "desc": "",
+        "dyn": 0,
+        "extendNew": 10,
+        "extendRev": 50,
+        "id": id,
+        "lrnToday": [0, 0],
+        "mod": now_sec,
+        "name": name,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "timeToday": [0, 0],
+        "usn": -1,
+    })
+}
+
+fn field(name: &str, ord: usize) -> serde_json::Value {
+    json!({
+        "font": "Arial",
+        "media": [],
+        "name": name,
+        "ord": ord,
+        "rtl": false,
+        "size": 20,
+        "sticky": false,
+    })
+}
+
+fn make_note(word: &str, entries: &[Entry], level: Option<u8>) -> Note {
+    let mut classes = vec![];
+    let mut definitions = vec![];
+    let mut examples = vec![];
+
+    for entry in entries {
+        for definition in &entry.definitions {
+            let mut texts = vec![];
+            for text in &definition.content {
+                match text {
+                    Text::Class(s) if !classes.contains(&s) => classes.push(s),
+                    Text::Definition(s) => texts.push(s.as_str()),
+                    Text::Example(s) => examples.push(s.as_str()),
+                    _ => (),
+                }
+            }
+            if !texts.is_empty() {
+                definitions.push(texts.join(" "));
+            }
+        }
+    }
+
+    let mut front = format!("<div class=\"eitaro-key\">{}</div>", escape(word, Html));
+    if !classes.is_empty() {
+        let classes: Vec<&str> = classes.iter().map(|it| it.as_str()).collect();
+        front.push_str(&format!("<div class=\"eitaro-class\">{}</div>", escape(&classes.join(" "), Html)));
+    }
+
+    let mut back = "<ol class=\"eitaro-definitions\">".to_owned();
+    for definition in &definitions {
+        back.push_str(&format!("<li>{}</li>", escape(definition, Html)));
+    }
+    back.push_str("</ol>");
+    for example in &examples {
+        back.push_str(&format!("<div class=\"eitaro-example\">{}</div>", escape(example, Html)));
+    }
+    if let Some(level) = level {
+        back.push_str(&format!("<div class=\"eitaro-level\">SVL {}</div>", level));
+    }
+
+    Note { front, back, sort_field: word.to_owned() }
+}
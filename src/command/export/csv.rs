@@ -2,20 +2,22 @@ use std::io::Write;
 
 use crate::errors::AppResultU;
 use crate::dictionary::Dictionary;
-use super::Exporter;
+use super::{Exporter, lookup, Options};
 
 
 
 const MAX_LENGTH: usize = 500;
 
-pub struct CsvExporter();
+pub struct CsvExporter {
+    pub options: Options,
+}
 
 impl Exporter for CsvExporter {
-    fn export<T: Write>(&self, dictionary: &mut Dictionary, words: &[&str], out: &mut T) -> AppResultU {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU {
         let mut out = csv::Writer::from_writer(out);
 
         for word in words {
-            if let Some(entries) = dictionary.get(&word)? {
+            if let Some(entries) = lookup(dictionary, word, &self.options)? {
                 let mut buf = "".to_owned();
 
                 'outer: for entry in entries {
@@ -37,8 +39,6 @@ impl Exporter for CsvExporter {
                 }
 
                 out.write_record(&[word, buf.as_str()])?;
-            } else {
-                eprintln!("Definition not found: {}", word);
             }
         }
 
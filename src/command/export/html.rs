@@ -0,0 +1,40 @@
+use std::io::Write;
+
+use crate::command::html::write_entries;
+use crate::errors::AppResultU;
+use crate::dictionary::Dictionary;
+use crate::theme;
+use super::{Exporter, lookup, Options};
+
+
+
+const TITLE: &str = "Eitaro";
+
+
+pub struct HtmlExporter {
+    pub options: Options,
+}
+
+impl Exporter for HtmlExporter {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU {
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html>")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>{}</title>", TITLE)?;
+        writeln!(out, "<style>\n{}</style>", theme::current().css())?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+
+        for word in words {
+            if let Some(entries) = lookup(dictionary, word, &self.options)? {
+                write_entries(out, &entries)?;
+            }
+        }
+
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::Write;
+
+use serde_derive::Serialize;
+
+use crate::errors::AppResultU;
+use crate::dictionary::{Dictionary, Entry};
+use super::{Exporter, lookup, Options};
+
+
+
+pub struct JsonExporter {
+    pub options: Options,
+}
+
+#[derive(Serialize)]
+struct Word<'a> {
+    word: &'a str,
+    level: Option<u8>,
+    entries: Vec<Entry>,
+}
+
+impl Exporter for JsonExporter {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU {
+        let mut result = vec![];
+
+        for &word in words {
+            if let Some(entries) = lookup(dictionary, word, &self.options)? {
+                let level = dictionary.get_level(word)?;
+                result.push(Word { word, level, entries });
+            }
+        }
+
+        serde_json::to_writer_pretty(&mut *out, &result)?;
+        writeln!(out)?;
+
+        Ok(())
+    }
+}
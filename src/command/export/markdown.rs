@@ -0,0 +1,62 @@
+use std::io::Write;
+
+use crate::errors::AppResultU;
+use crate::dictionary::{Definition, Dictionary, Text};
+use super::{Exporter, lookup, Options};
+
+
+
+pub struct MarkdownExporter {
+    pub options: Options,
+}
+
+impl Exporter for MarkdownExporter {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU {
+        for word in words {
+            if let Some(entries) = lookup(dictionary, word, &self.options)? {
+                writeln!(out, "## {}", word)?;
+                writeln!(out)?;
+                for entry in entries {
+                    if entry.key != *word {
+                        writeln!(out, "### {}", entry.key)?;
+                        writeln!(out)?;
+                    }
+                    for definition in &entry.definitions {
+                        write_definition(out, definition)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_definition<T: Write>(out: &mut T, definition: &Definition) -> AppResultU {
+    use self::Text::*;
+
+    let mut line = vec![];
+    let mut children = vec![];
+
+    for text in &definition.content {
+        match text {
+            Annot(s) | Information(s) | Note(s) => line.push(s.to_owned()),
+            Class(s) => line.push(format!("*{}*", s)),
+            Countability(c) => line.push(format!("[{}]", c)),
+            Definition(s) => line.push(format!("**{}**", s)),
+            Error(s) => line.push(format!("~~{}~~", s)),
+            Etymology(s) => children.push(format!("語源: {}", s)),
+            Example(s) => children.push(format!("> {}", s)),
+            Tag(s) => line.push(format!("`{}`", s)),
+            Word(s) => line.push(s.to_owned()),
+        }
+    }
+
+    writeln!(out, "- {}", line.join(" "))?;
+    for child in children {
+        writeln!(out, "    - {}", child)?;
+    }
+
+    Ok(())
+}
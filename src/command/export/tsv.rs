@@ -0,0 +1,48 @@
+use std::io::Write;
+
+use crate::errors::AppResultU;
+use crate::dictionary::{Dictionary, Text};
+use crate::screen::format::tsv_field;
+use super::{Exporter, lookup, Options};
+
+
+
+const SEPARATOR: &str = " / ";
+
+/// word, key, class, definition, note, example, etymology
+pub struct TsvExporter {
+    pub options: Options,
+}
+
+impl Exporter for TsvExporter {
+    fn export<T: Write>(&self, dictionary: &Dictionary, words: &[&str], out: &mut T) -> AppResultU {
+        use self::Text::*;
+
+        for word in words {
+            if let Some(entries) = lookup(dictionary, word, &self.options)? {
+                for entry in entries {
+                    for definition in &entry.definitions {
+                        let mut columns: [Vec<String>; 5] = Default::default();
+                        for text in &definition.content {
+                            match text {
+                                Class(s) => columns[0].push(s.to_owned()),
+                                Definition(s) => columns[1].push(s.to_owned()),
+                                Annot(s) | Error(s) | Information(s) | Note(s) | Tag(s) | Word(s) => columns[2].push(s.to_owned()),
+                                Countability(c) => columns[2].push(c.to_string()),
+                                Example(s) => columns[3].push(s.to_owned()),
+                                Etymology(s) => columns[4].push(s.to_owned()),
+                            }
+                        }
+                        write!(out, "{}\t{}", tsv_field(word), tsv_field(&entry.key))?;
+                        for column in &columns {
+                            write!(out, "\t{}", tsv_field(&column.join(SEPARATOR)))?;
+                        }
+                        writeln!(out)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
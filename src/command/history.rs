@@ -0,0 +1,153 @@
+
+use std::collections::HashMap;
+use std::io::{stdout, Write};
+use std::path::Path;
+
+use separator::Separatable;
+use serde::Serialize;
+use structopt::StructOpt;
+
+use crate::dictionary::Dictionary;
+use crate::errors::{AppError, AppResultU};
+
+
+
+const INDENT: &str = "    ";
+
+
+#[derive(Debug, StructOpt)]
+pub struct Opt {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Export as CSV or JSON
+    Export(ExportOpt),
+    /// List looked up words (newest first)
+    List(ListOpt),
+    /// Show statistics
+    Stats(StatsOpt),
+}
+
+#[derive(Debug, Default, StructOpt)]
+pub struct ListOpt {
+    /// Take only n items
+    #[structopt(short, long)]
+    n: Option<usize>,
+    /// Only not found words
+    #[structopt(long = "not-found")]
+    not_found: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct StatsOpt {
+    /// Show top n words
+    #[structopt(short, long, default_value = "20")]
+    n: usize,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ExportOpt {
+    /// Output format
+    #[structopt(short, long, default_value = "csv", possible_values = &["csv", "json"])]
+    format: String,
+}
+
+
+pub fn history<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    use self::Command::*;
+
+    let dic = Dictionary::new(dictionary_paths);
+
+    match opt.command.unwrap_or_else(|| List(ListOpt::default())) {
+        Export(opt) =>
+            export_items(&opt, &dic.history(None)?),
+        List(opt) =>
+            list(&dic, &opt),
+        Stats(opt) =>
+            stats(&dic, &opt),
+    }
+}
+
+pub fn export_items<T: Serialize>(opt: &ExportOpt, items: &[T]) -> AppResultU {
+    let out = stdout();
+    let mut out = out.lock();
+
+    match &*opt.format {
+        "csv" => {
+            let mut out = csv::Writer::from_writer(out);
+            for item in items {
+                out.serialize(item)?;
+            }
+            out.flush()?;
+        },
+        "json" => {
+            serde_json::to_writer_pretty(&mut out, items)?;
+            writeln!(out)?;
+        },
+        _ => return Err(AppError::Eitaro("Unknown format")),
+    }
+
+    Ok(())
+}
+
+fn list(dic: &Dictionary, opt: &ListOpt) -> AppResultU {
+    // The not found words are filtered after loading
+    let limit = if opt.not_found { None } else { opt.n };
+    let items = dic.history(limit)?;
+    let items = items.iter().filter(|it| !opt.not_found || !it.found);
+
+    for item in items.take(opt.n.unwrap_or(std::usize::MAX)) {
+        print!("{}  {:5}  {}", item.created_at, item.source, item.term);
+        if item.found {
+            println!();
+        } else {
+            println!(" (not found)");
+        }
+    }
+
+    Ok(())
+}
+
+fn stats(dic: &Dictionary, opt: &StatsOpt) -> AppResultU {
+    let items = dic.history(None)?;
+
+    let mut not_found = 0;
+    let mut sources = HashMap::<&str, usize>::new();
+    let mut words = HashMap::<&str, usize>::new();
+
+    for item in &items {
+        if !item.found {
+            not_found += 1;
+        }
+        *sources.entry(item.source.as_str()).or_default() += 1;
+        *words.entry(item.term.as_str()).or_default() += 1;
+    }
+
+    println!("Lookups:");
+    println!("{}{:<17}{:>7}", INDENT, "Total", items.len().separated_string());
+    println!("{}{:<17}{:>7}", INDENT, "Unique", words.len().separated_string());
+    println!("{}{:<17}{:>7}", INDENT, "Not found", not_found.separated_string());
+    println!();
+
+    let mut sources: Vec<(&str, usize)> = sources.into_iter().collect();
+    sources.sort();
+    println!("Sources:");
+    for (source, count) in sources {
+        println!("{}{:<17}{:>7}", INDENT, source, count.separated_string());
+    }
+    println!();
+
+    let mut words: Vec<(&str, usize)> = words.into_iter().collect();
+    words.sort_by(|(a_word, a), (b_word, b)| b.cmp(a).then_with(|| a_word.cmp(b_word)));
+    let width = opt.n.to_string().len();
+    println!("Top words:");
+    for (index, (word, count)) in words.iter().take(opt.n).enumerate() {
+        println!("{}{:width$}. {:16} {:>7}", INDENT, index + 1, word, count.separated_string(), width = width);
+    }
+    println!();
+
+    Ok(())
+}
@@ -5,8 +5,9 @@ use std::path::Path;
 use askama_escape::{escape, Html};
 use structopt::StructOpt;
 
-use crate::dictionary::{Dictionary, Entry, Text};
+use crate::dictionary::{dictionary_header, Definition, Dictionary, Entry, Text};
 use crate::errors::AppError;
+use crate::theme;
 
 
 
@@ -14,59 +15,41 @@ use crate::errors::AppError;
 pub struct Opt {
     /// Word
     word: String,
+    /// Put the stylesheet of the theme
+    #[structopt(short, long)]
+    style: bool,
 }
 
-pub fn lookup<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> Result<(), AppError> {
-    let mut dic = Dictionary::new(dictionary_path);
+pub fn lookup<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> Result<(), AppError> {
+    let dic = Dictionary::new(dictionary_paths);
     let found = dic.get_smart(opt.word.trim())?.ok_or(AppError::NotFound)?;
-    print(found)?;
+    print(found, opt.style)?;
     Ok(())
 }
 
-fn print(entries: Vec<Entry>) -> Result<(), AppError> {
-    fn span<W: Write>(out: &mut W, name: &'static str, text: &str) -> Result<(), IOError> {
-        write!(out, "<span class=\"eitaro-definition eitaro-def-{}\">{}</span>", name, escape(text, Html))
-    }
-
-    fn color_key<W: Write>(out: &mut W, key: &str) -> Result<(), IOError> {
-        span(out, "key", key)
-    }
-
-    fn color<W: Write>(out: &mut W, text: &Text) -> Result<(), IOError> {
-        use self::Text::*;
-
-        match text {
-            Annot(s) => span(out, "annotation", s),
-            Class(s) => span(out, "class", s),
-            Countability(c) => span(out, "countability", &format!("{}", c)),
-            Definition(s) => span(out, "definition", s),
-            Error(s) => span(out, "error", s),
-            Etymology(s) => span(out, "etymology", s),
-            Example(s) => span(out, "example", s),
-            Information(s) => span(out, "information", s),
-            Note(s) => span(out, "note", s),
-            Tag(s) => span(out, "tag", s),
-            Word(s) => color_key(out, &s),
-        }
-    }
-
+fn print(entries: Vec<Entry>, style: bool) -> Result<(), AppError> {
     let out = stdout();
     let out = out.lock();
     let mut out = BufWriter::new(out);
+    if style {
+        writeln!(out, "<style>\n{}</style>", theme::current().css())?;
+    }
+    write_entries(&mut out, &entries)?;
+    Ok(())
+}
 
-    for entry in entries {
+pub fn write_entries<W: Write>(out: &mut W, entries: &[Entry]) -> Result<(), IOError> {
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(name) = dictionary_header(entries, index) {
+            writeln!(out, "<h2 class=\"eitaro-dictionary\">{}</h2>", escape(name, Html))?;
+        }
         writeln!(out, "<h1 class=\"eitaro-term\">{}</h1>", escape(&entry.key, Html))?;
 
         writeln!(out, "<ol>")?;
 
         for definition in &entry.definitions {
             write!(out, "  <li>")?;
-            for (index, text) in definition.content.iter().enumerate() {
-                if 0 < index {
-                    write!(out, " ")?;
-                }
-                color(&mut out, text)?;
-            }
+            write_definition(out, definition)?;
             writeln!(out, "  </li>")?;
         }
 
@@ -75,3 +58,39 @@ fn print(entries: Vec<Entry>) -> Result<(), AppError> {
 
     Ok(())
 }
+
+pub fn write_definition<W: Write>(out: &mut W, definition: &Definition) -> Result<(), IOError> {
+    if let Some(source) = &definition.source {
+        span(out, "source", source)?;
+        write!(out, " ")?;
+    }
+    for (index, text) in definition.content.iter().enumerate() {
+        if 0 < index {
+            write!(out, " ")?;
+        }
+        write_text(out, text)?;
+    }
+    Ok(())
+}
+
+pub fn write_text<W: Write>(out: &mut W, text: &Text) -> Result<(), IOError> {
+    use self::Text::*;
+
+    match text {
+        Annot(s) => span(out, "annotation", s),
+        Class(s) => span(out, "class", s),
+        Countability(c) => span(out, "countability", &format!("{}", c)),
+        Definition(s) => span(out, "definition", s),
+        Error(s) => span(out, "error", s),
+        Etymology(s) => span(out, "etymology", s),
+        Example(s) => span(out, "example", s),
+        Information(s) => span(out, "information", s),
+        Note(s) => span(out, "note", s),
+        Tag(s) => span(out, "tag", s),
+        Word(s) => span(out, "key", s),
+    }
+}
+
+fn span<W: Write>(out: &mut W, name: &'static str, text: &str) -> Result<(), IOError> {
+    write!(out, "<span class=\"eitaro-definition eitaro-def-{}\">{}</span>", name, escape(text, Html))
+}
@@ -1,15 +1,25 @@
 
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use actix_cors::Cors;
-use actix_web::{App,  HttpServer, Responder, web, http::header};
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer, Responder, web, http::header};
 use serde_derive::*;
 use structopt::StructOpt;
 
-use crate::dictionary::Dictionary;
-use crate::errors::AppError;
+use crate::config;
+use crate::dictionary::{dictionary_header, Dictionary, Entry, Filter};
+use crate::errors::{AppError, AppResult};
+use crate::history::Source;
 use crate::screen::{Screen, Opt as ScreenOpt};
+use crate::screen::plain::write_definition;
 
+mod error;
+
+
+
+const REVERSE_LOOKUP_LIMIT: usize = 20;
 
 
 #[derive(StructOpt, Debug)]
@@ -20,14 +30,14 @@ pub struct Opt {
     /// Ignore not found
     #[structopt(short = "i", long = "ignore")]
     pub ignore_not_found: bool,
-    /// Output to
+    /// Output to (Default: `server.screen` in config)
     #[structopt(subcommand)]
-    pub screen: ScreenOpt,
+    pub screen: Option<ScreenOpt>,
 }
 
 #[derive(Clone)]
 struct State {
-    pub dictionary_path: PathBuf,
+    pub dictionary: Arc<Dictionary>,
     pub ignore_not_found: bool,
     pub screen: Screen,
 }
@@ -37,12 +47,54 @@ pub struct GetWord {
     word: String,
 }
 
-pub fn start_server(opt: Opt, dictionary_path: PathBuf) -> Result<(), AppError> {
-    let bind_to = opt.bind_to.unwrap_or_else(|| "127.0.0.1:8116".to_owned());
+#[derive(Deserialize)]
+pub struct Query {
+    q: String,
+}
+
+/// `?source=a,b&exclude_source=c&tag=d`
+#[derive(Deserialize)]
+pub struct FilterQuery {
+    source: Option<String>,
+    exclude_source: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GetTag {
+    tag: String,
+}
+
+#[derive(Serialize)]
+struct Level {
+    word: String,
+    level: u8,
+}
+
+#[derive(Serialize)]
+struct Lemma {
+    word: String,
+    lemma: String,
+}
+
+#[derive(Serialize)]
+struct TagCount {
+    tag: String,
+    count: usize,
+}
+
+pub fn start_server(opt: Opt, dictionary_paths: Vec<PathBuf>) -> Result<(), AppError> {
+    let config = &config::get().server;
+    let bind_to = opt.bind_to.or_else(|| config.bind_to.clone()).unwrap_or_else(|| "127.0.0.1:8116".to_owned());
+    let screen = match (opt.screen, &config.screen) {
+        (Some(screen), _) => screen,
+        (None, Some(name)) => ScreenOpt::from_name(name)?,
+        (None, None) => return Err(AppError::Eitaro("No screen")),
+    };
     let state = State {
-        dictionary_path: dictionary_path.clone(),
-        ignore_not_found: opt.ignore_not_found,
-        screen: Screen::new(opt.screen, dictionary_path, bind_to.clone())
+        dictionary: Arc::new(Dictionary::new(&dictionary_paths)),
+        ignore_not_found: opt.ignore_not_found || config.ignore,
+        screen: Screen::new(screen, dictionary_paths, bind_to.clone())
     };
     let server = HttpServer::new(move || {
         let state= state.clone();
@@ -57,6 +109,14 @@ pub fn start_server(opt: Opt, dictionary_path: PathBuf) -> Result<(), AppError>
             )
             .route("/ack", web::get().to(on_ack))
             .route("/word/{word}", web::get().to(on_get_word))
+            .route("/search", web::get().to(on_search))
+            .route("/like", web::get().to(on_like))
+            .route("/rlookup/{word}", web::get().to(on_reverse_lookup))
+            .route("/level/{word}", web::get().to(on_level))
+            .route("/lemma/{word}", web::get().to(on_lemma))
+            .route("/suggest/{word}", web::get().to(on_suggest))
+            .route("/tags", web::get().to(on_tags))
+            .route("/tag/{tag}", web::get().to(on_tag))
             .data(state)
     });
 
@@ -67,28 +127,122 @@ pub fn start_server(opt: Opt, dictionary_path: PathBuf) -> Result<(), AppError>
     Ok(())
 }
 
+impl FilterQuery {
+    fn to_filter(&self) -> Filter {
+        Filter::new(split_names(&self.source), split_names(&self.exclude_source)).with_tags(split_names(&self.tag))
+    }
+}
+
+
+
 fn on_ack() -> impl Responder {
     "␆"
 }
 
-fn on_get_word(state: web::Data<State>, param: web::Path<GetWord>) -> impl Responder {
-    match Dictionary::get_word(&state.dictionary_path, &param.word) {
-        Ok(entries) => {
-            if !state.ignore_not_found || entries.is_some() {
-                state.screen.print_opt(entries.clone());
-            }
-            if let Some(entries) = entries {
-                let mut content = vec![];
-                for entry in &entries {
-                    content.push(format!("#{}", entry.key));
-                    // content.push(entry.content);
-                }
-
-                Some(content.join("\n"))
-            } else {
-                None
-            }
-        },
-        Err(err) => panic!("Not implemented: {}", err)
+fn on_get_word(request: HttpRequest, state: web::Data<State>, param: web::Path<GetWord>, filter: web::Query<FilterQuery>) -> AppResult<HttpResponse> {
+    let entries = state.dictionary.get_smart_with(&param.word, &filter.to_filter())?;
+    if let Err(error) = state.dictionary.record_history(&param.word, Source::Http, entries.is_some()) {
+        eprintln!("Failed to record history: {}", error);
+    }
+
+    if !state.ignore_not_found || entries.is_some() {
+        state.screen.print_opt(entries.clone());
+    }
+
+    let entries = entries.ok_or(AppError::NotFound)?;
+
+    if prefers_json(&request) {
+        Ok(HttpResponse::Ok().json(entries))
+    } else {
+        Ok(HttpResponse::Ok()
+           .content_type("text/plain; charset=utf-8")
+           .body(plain_text(&entries)))
     }
 }
+
+fn on_search(state: web::Data<State>, query: web::Query<Query>, filter: web::Query<FilterQuery>) -> AppResult<HttpResponse> {
+    let entries = state.dictionary.search_with(&query.q, &filter.to_filter())?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+fn on_like(state: web::Data<State>, query: web::Query<Query>, filter: web::Query<FilterQuery>) -> AppResult<HttpResponse> {
+    let entries = state.dictionary.like_with(&query.q, &filter.to_filter())?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+fn on_reverse_lookup(state: web::Data<State>, param: web::Path<GetWord>, filter: web::Query<FilterQuery>) -> AppResult<HttpResponse> {
+    let entries = state.dictionary.reverse_lookup_with(&param.word, REVERSE_LOOKUP_LIMIT, &filter.to_filter())?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+fn on_level(state: web::Data<State>, param: web::Path<GetWord>) -> AppResult<HttpResponse> {
+    let level = state.dictionary.get_level(&param.word)?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(Level { word: param.word.clone(), level }))
+}
+
+fn on_lemma(state: web::Data<State>, param: web::Path<GetWord>) -> AppResult<HttpResponse> {
+    let lemma = state.dictionary.lemmatize(&param.word)?;
+    Ok(HttpResponse::Ok().json(Lemma { word: param.word.clone(), lemma }))
+}
+
+fn on_suggest(state: web::Data<State>, param: web::Path<GetWord>) -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(state.dictionary.correct(&param.word)))
+}
+
+fn on_tags(state: web::Data<State>) -> AppResult<HttpResponse> {
+    let tags: Vec<TagCount> = state.dictionary.tags()?.into_iter().map(|(tag, count)| TagCount { tag, count }).collect();
+    Ok(HttpResponse::Ok().json(tags))
+}
+
+fn on_tag(state: web::Data<State>, param: web::Path<GetTag>) -> AppResult<HttpResponse> {
+    let terms = state.dictionary.tagged(&param.tag)?;
+    if terms.is_empty() {
+        return Err(AppError::NotFound);
+    }
+    Ok(HttpResponse::Ok().json(terms))
+}
+
+fn plain_text(entries: &[Entry]) -> String {
+    let mut content = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(name) = dictionary_header(entries, index) {
+            writeln!(content, "== {} ==", name).unwrap();
+        }
+        writeln!(content, "#{}", entry.key).unwrap();
+        for definition in &entry.definitions {
+            write_definition(&mut content, definition).unwrap();
+            writeln!(content).unwrap();
+        }
+    }
+    String::from_utf8_lossy(&content).into_owned()
+}
+
+fn split_names(names: &Option<String>) -> Vec<String> {
+    names.as_ref().map(|it| {
+        it.split(',').map(str::trim).filter(|it| !it.is_empty()).map(str::to_owned).collect()
+    }).unwrap_or_default()
+}
+
+fn prefers_json(request: &HttpRequest) -> bool {
+    request.headers()
+        .get(header::ACCEPT)
+        .and_then(|it| it.to_str().ok())
+        .map_or(false, accepts_json)
+}
+
+fn accepts_json(accept: &str) -> bool {
+    accept.split(',').any(|it| {
+        let media_type = it.split(';').next().unwrap_or("").trim();
+        media_type.eq_ignore_ascii_case("application/json")
+    })
+}
+
+
+
+#[cfg(test)]#[test]
+fn test_accepts_json() {
+    assert!(accepts_json("application/json"));
+    assert!(accepts_json("text/html, application/json;q=0.9"));
+    assert!(!accepts_json("*/*"));
+    assert!(!accepts_json("text/plain"));
+}
@@ -0,0 +1,47 @@
+
+use actix_web::{HttpResponse, ResponseError};
+use actix_web::http::StatusCode;
+use diesel::result::Error as DieselError;
+use serde_derive::*;
+
+use crate::errors::AppError;
+
+
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: &'static str,
+    message: String,
+}
+
+
+impl ResponseError for AppError {
+    fn error_response(&self) -> HttpResponse {
+        let (status, error) = classify(self);
+        HttpResponse::build(status).json(ErrorBody { error, message: self.to_string() })
+    }
+
+    fn render_response(&self) -> HttpResponse {
+        self.error_response()
+    }
+}
+
+
+fn classify(error: &AppError) -> (StatusCode, &'static str) {
+    use self::AppError::*;
+
+    match error {
+        NotFound =>
+            (StatusCode::NOT_FOUND, "not_found"),
+        Diesel(DieselError::DatabaseError(_, info)) if info.message().starts_with("no such table") =>
+            (StatusCode::SERVICE_UNAVAILABLE, "dictionary_not_built"),
+        DieselConnection(_) | Pool(_) =>
+            (StatusCode::SERVICE_UNAVAILABLE, "dictionary_unavailable"),
+        Diesel(_) =>
+            (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+        FloatingNumberFormat(_) | NumberFormat(_) | Pom(_) | Regex(_) =>
+            (StatusCode::BAD_REQUEST, "invalid_query"),
+        _ =>
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+    }
+}
@@ -16,8 +16,8 @@ pub struct Opt {
 }
 
 
-pub fn lemmas<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
+pub fn lemmas<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
     let keys = dic.keys()?;
     let keys: HashSet<&String> = keys.iter().collect();
     let keys: Vec<&String> = keys.into_iter().collect();
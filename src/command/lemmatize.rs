@@ -15,8 +15,8 @@ pub struct Opt {
 }
 
 
-pub fn lemmatize<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
+pub fn lemmatize<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
     println!("{}", dic.lemmatize(&opt.word)?);
     Ok(())
 }
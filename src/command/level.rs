@@ -1,10 +1,12 @@
 
 use std::path::Path;
 
+use serde_derive::Serialize;
 use structopt::StructOpt;
 
 use crate::dictionary::Dictionary;
 use crate::errors::AppResultU;
+use crate::screen::format::{Format, FORMAT_NAMES, tsv_field};
 
 
 
@@ -12,15 +14,45 @@ use crate::errors::AppResultU;
 pub struct Opt {
     /// Level
     word: String,
+    /// Output format
+    #[structopt(short, long, possible_values = FORMAT_NAMES)]
+    format: Option<Format>,
 }
 
+#[derive(Serialize)]
+struct Level<'a> {
+    word: &'a str,
+    level: Option<u8>,
+}
+
+
+pub fn level<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
+    let found = dic.get_level(&opt.word)?;
 
-pub fn level<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
-    if let Some(found) = dic.get_level(&opt.word)? {
+    if let Some(format) = opt.format {
+        print_level(format, &Level { word: &opt.word, level: found })?;
+    } else if let Some(found) = found {
         println!("{}", found);
     } else {
         eprintln!("Not available");
     }
     Ok(())
 }
+
+fn print_level(format: Format, level: &Level) -> AppResultU {
+    use self::Format::*;
+
+    let level_text = level.level.map(|it| it.to_string()).unwrap_or_default();
+
+    match format {
+        Json | Jsonl =>
+            println!("{}", serde_json::to_string(level)?),
+        Markdown =>
+            println!("- {}: {}", level.word, level_text),
+        Tsv =>
+            println!("{}\t{}", tsv_field(level.word), level_text),
+    }
+
+    Ok(())
+}
@@ -1,19 +1,45 @@
 
 use std::fs::OpenOptions;
-use std::io::{stdin, stdout, Write};
+use std::io::{BufRead, BufWriter, stdin, stdout, Write};
 use std::path::Path;
 
 use rustyline;
 use structopt::StructOpt;
 
-use crate::dictionary::Dictionary;
-use crate::errors::{AppResult, AppResultU};
+use crate::config;
+use crate::dictionary::{Dictionary, Entry, Filter};
+use crate::errors::{AppError, AppResult, AppResultU};
+use crate::history::Source;
 use crate::path::get_history_path;
 use crate::screen;
+use crate::screen::format::{self, Format, FORMAT_NAMES, RecordWriter};
 
 
 
 const DEFAULT_PROMPT: &str = "Eitaro> ";
+const DEFAULT_REVERSE_LIMIT: usize = 20;
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    Like,
+    Lookup,
+    Reverse,
+}
+
+
+#[derive(Debug, Default, StructOpt)]
+pub struct FilterOpt {
+    /// Use only the given sources (comma separated)
+    #[structopt(long = "source", use_delimiter = true)]
+    source: Vec<String>,
+    /// Ignore the given sources (comma separated)
+    #[structopt(long = "exclude-source", use_delimiter = true)]
+    exclude_source: Vec<String>,
+    /// Only the definitions having the given tags (comma separated)
+    #[structopt(long = "tag", use_delimiter = true)]
+    tag: Vec<String>,
+}
 
 
 #[derive(Debug, StructOpt)]
@@ -25,14 +51,23 @@ pub struct LikeOpt {
     color: bool,
     /// Take only n related entries
     #[structopt(short, long)]
-    n: Option<usize>
+    n: Option<usize>,
+    /// Output format
+    #[structopt(short, long, possible_values = FORMAT_NAMES)]
+    format: Option<Format>,
+    #[structopt(flatten)]
+    filter: FilterOpt,
 }
 
 
 #[derive(Debug, StructOpt)]
 pub struct LookupOpt {
     /// Word
-    word: String,
+    #[structopt(required_unless = "batch")]
+    word: Option<String>,
+    /// Read queries from STDIN (one per line)
+    #[structopt(short, long, conflicts_with = "word")]
+    batch: bool,
     /// No Color
     #[structopt(long="no-color", parse(from_flag = std::ops::Not::not))]
     color: bool,
@@ -40,7 +75,30 @@ pub struct LookupOpt {
     correction: bool,
     /// Take only n related entries
     #[structopt(short, long)]
-    n: Option<usize>
+    n: Option<usize>,
+    /// Output format
+    #[structopt(short, long, possible_values = FORMAT_NAMES)]
+    format: Option<Format>,
+    #[structopt(flatten)]
+    filter: FilterOpt,
+}
+
+
+#[derive(Debug, StructOpt)]
+pub struct ReverseLookupOpt {
+    /// Japanese word
+    word: String,
+    /// No Color
+    #[structopt(long="no-color", parse(from_flag = std::ops::Not::not))]
+    color: bool,
+    /// Take only n related entries
+    #[structopt(short, long)]
+    n: Option<usize>,
+    /// Output format
+    #[structopt(short, long, possible_values = FORMAT_NAMES)]
+    format: Option<Format>,
+    #[structopt(flatten)]
+    filter: FilterOpt,
 }
 
 
@@ -49,20 +107,37 @@ pub struct ShellOpt {
     /// Prompt text
     #[structopt(short, long, env="EITARO_PROMPT")]
     prompt: Option<String>,
+    #[structopt(flatten)]
+    filter: FilterOpt,
 }
 
 
-pub fn like<T: AsRef<Path>>(opt: LikeOpt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
-    lookup_and_print(&mut dic, &opt.word, true, opt.color, opt.n, false, true)
+pub fn like<T: AsRef<Path>>(opt: LikeOpt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths).with_filter(opt.filter.into());
+    lookup_and_print(&dic, &opt.word, Mode::Like, opt.color, opt.format, opt.n, false)?;
+    Ok(())
+}
+
+pub fn lookup<T: AsRef<Path>>(opt: LookupOpt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths).with_filter(opt.filter.into());
+
+    if opt.batch {
+        return batch(&dic, opt.format, opt.n);
+    }
+
+    let word = opt.word.ok_or(AppError::Eitaro("No word"))?;
+    let found = lookup_and_print(&dic, &word, Mode::Lookup, opt.color, opt.format, opt.n, opt.correction && opt.format.is_none())?;
+    record_history(&dic, &word, Source::Cli, found);
+    Ok(())
 }
 
-pub fn lookup<T: AsRef<Path>>(opt: LookupOpt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
-    lookup_and_print(&mut dic, &opt.word, false, opt.color, opt.n, opt.correction, true)
+pub fn reverse_lookup<T: AsRef<Path>>(opt: ReverseLookupOpt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths).with_filter(opt.filter.into());
+    lookup_and_print(&dic, &opt.word, Mode::Reverse, opt.color, opt.format, opt.n, false)?;
+    Ok(())
 }
 
-pub fn shell<T: AsRef<Path>>(opt: ShellOpt, dictionary_path: &T) -> AppResultU {
+pub fn shell<T: AsRef<Path>>(opt: ShellOpt, dictionary_paths: &[T]) -> AppResultU {
     let config = rustyline::config::Builder::new()
         .auto_add_history(true)
         .build();
@@ -72,8 +147,8 @@ pub fn shell<T: AsRef<Path>>(opt: ShellOpt, dictionary_path: &T) -> AppResultU {
         editor.load_history(&history_path)?;
     }
 
-    let mut dic = Dictionary::new(dictionary_path);
-    let prompt = opt.prompt.unwrap_or_else(|| DEFAULT_PROMPT.to_owned());
+    let dic = Dictionary::new(dictionary_paths).with_filter(opt.filter.into());
+    let prompt = opt.prompt.or_else(|| config::get().prompt.clone()).unwrap_or_else(|| DEFAULT_PROMPT.to_owned());
     loop {
         match editor.readline(&prompt) {
             Ok(ref input) => {
@@ -81,7 +156,8 @@ pub fn shell<T: AsRef<Path>>(opt: ShellOpt, dictionary_path: &T) -> AppResultU {
                 if input.is_empty() {
                     continue;
                 }
-                lookup_and_print(&mut dic, input, false, true, None, true, true)?;
+                let found = lookup_and_print(&dic, input, Mode::Lookup, true, None, None, true)?;
+                record_history(&dic, input, Source::Shell, found);
                 let _ = append_history(input);
             },
             Err(rustyline::error::ReadlineError::Eof) => {
@@ -96,31 +172,77 @@ pub fn shell<T: AsRef<Path>>(opt: ShellOpt, dictionary_path: &T) -> AppResultU {
     Ok(())
 }
 
-fn lookup_and_print(dic: &mut Dictionary, word: &str, like: bool, color: bool, limit: Option<usize>, correction: bool, pager: bool) -> AppResultU {
-    let mut found = if like {
-        dic.like(word.trim())
-    } else if word.starts_with('/') {
-        dic.search(word[1..].trim())
-    } else {
-        dic.get_smart(word.trim())
+impl From<FilterOpt> for Filter {
+    fn from(opt: FilterOpt) -> Self {
+        Filter::new(opt.source, opt.exclude_source).with_tags(opt.tag)
+    }
+}
+
+
+
+fn batch(dic: &Dictionary, format: Option<Format>, limit: Option<usize>) -> AppResultU {
+    let input = stdin();
+    let input = input.lock();
+    let out = stdout();
+    let out = BufWriter::new(out.lock());
+    let mut writer = RecordWriter::new(out, format);
+
+    for line in input.lines() {
+        let line = line?;
+        let query = line.trim();
+        if query.is_empty() {
+            continue;
+        }
+        let found = find(dic, query, Mode::Lookup, limit)?;
+        writer.write(query, found.as_ref().map(Vec::as_slice))?;
+    }
+
+    writer.finish()
+}
+
+fn find(dic: &Dictionary, word: &str, mode: Mode, limit: Option<usize>) -> AppResult<Option<Vec<Entry>>> {
+    let mut found = match mode {
+        Mode::Like =>
+            dic.like(word.trim()),
+        Mode::Reverse =>
+            dic.reverse_lookup(word.trim(), limit.unwrap_or(DEFAULT_REVERSE_LIMIT)),
+        Mode::Lookup if word.starts_with('/') =>
+            dic.search(word[1..].trim()),
+        Mode::Lookup if word.starts_with('?') =>
+            dic.reverse_lookup(word[1..].trim(), limit.unwrap_or(DEFAULT_REVERSE_LIMIT)),
+        Mode::Lookup =>
+            dic.get_smart(word.trim()),
     }?;
 
     if let Some(limit) = limit {
         found = found.map(|it| it.into_iter().take(limit + 1).collect());
     }
 
+    Ok(found)
+}
+
+fn lookup_and_print(dic: &Dictionary, word: &str, mode: Mode, color: bool, format: Option<Format>, limit: Option<usize>, correction: bool) -> AppResult<bool> {
+    let found = find(dic, word, mode, limit)?;
+
+    if let Some(format) = format {
+        let out = stdout();
+        let mut out = out.lock();
+        format::write(&mut out, format, found.as_ref().map(Vec::as_slice).unwrap_or(&[]))?;
+        return Ok(found.is_some())
+    }
+
     if let Some(found) = found {
         if color {
             screen::color::print(found)?;
         } else {
             screen::plain::print(found)?;
         }
-        return Ok(())
+        return Ok(true)
     }
 
     if correction {
         if let Some(found) = untypo(dic, word)? {
-            return lookup_and_print(dic, &found, like, color, limit, false, pager);
+            return lookup_and_print(dic, &found, mode, color, format, limit, false);
         }
     }
 
@@ -130,10 +252,12 @@ fn lookup_and_print(dic: &mut Dictionary, word: &str, like: bool, color: bool, l
         screen::plain::print_not_found();
     }
 
-    Ok(())
+    Ok(false)
 }
 
-fn untypo(dic: &mut Dictionary, word: &str) -> AppResult<Option<String>> {
+fn untypo(dic: &Dictionary, word: &str) -> AppResult<Option<String>> {
+    println!("Correcting...");
+
     let candidates = dic.correct(word);
 
     if candidates.is_empty() {
@@ -171,6 +295,15 @@ fn untypo(dic: &mut Dictionary, word: &str) -> AppResult<Option<String>> {
     }
 }
 
+fn record_history(dic: &Dictionary, word: &str, source: Source, found: bool) {
+    // Queries for search and reverse lookup are not words
+    if word.starts_with('/') || word.starts_with('?') {
+        return;
+    }
+    if let Err(error) = dic.record_history(word, source, found) {
+        eprintln!("Failed to record history: {}", error);
+    }
+}
 
 fn append_history(line: &str) -> AppResultU {
     let path = get_history_path()?;
@@ -3,14 +3,20 @@ pub mod analyze;
 pub mod builder;
 pub mod completions;
 pub mod database;
+pub mod drill;
+pub mod dump;
 pub mod export;
+pub mod history;
 pub mod html;
 pub mod http;
 pub mod lemmas;
 pub mod lemmatize;
 pub mod level;
 pub mod lookup;
+pub mod notebook;
 pub mod path;
+pub mod tags;
 pub mod untypo;
+pub mod user;
 pub mod wordle;
 pub mod words;
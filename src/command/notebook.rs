@@ -0,0 +1,106 @@
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use separator::Separatable;
+use structopt::StructOpt;
+
+use crate::command::history::{export_items, ExportOpt};
+use crate::dictionary::Dictionary;
+use crate::errors::AppResultU;
+
+
+
+const INDENT: &str = "    ";
+
+
+#[derive(Debug, StructOpt)]
+pub struct Opt {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Star the word (and write a memo)
+    Add {
+        /// Word
+        word: String,
+        /// Memo
+        memo: Option<String>,
+    },
+    /// Export as CSV or JSON
+    Export(ExportOpt),
+    /// List starred words
+    List,
+    /// Unstar the word
+    Remove {
+        /// Word
+        word: String,
+    },
+    /// Show statistics
+    Stats,
+}
+
+
+pub fn notebook<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    use self::Command::*;
+
+    let dic = Dictionary::new(dictionary_paths);
+
+    match opt.command.unwrap_or(List) {
+        Add { word, memo } =>
+            dic.star(&word, memo.as_ref().map(String::as_str)),
+        Export(opt) =>
+            export_items(&opt, &dic.notebook()?),
+        List =>
+            list(&dic),
+        Remove { word } => {
+            if !dic.unstar(&word)? {
+                eprintln!("Not starred: {}", word);
+            }
+            Ok(())
+        },
+        Stats =>
+            stats(&dic),
+    }
+}
+
+fn list(dic: &Dictionary) -> AppResultU {
+    for item in dic.notebook()? {
+        if let Some(memo) = &item.memo {
+            println!("{}\t{}", item.term, memo);
+        } else {
+            println!("{}", item.term);
+        }
+    }
+    Ok(())
+}
+
+fn stats(dic: &Dictionary) -> AppResultU {
+    let items = dic.notebook()?;
+
+    let mut lookups = HashMap::<String, usize>::new();
+    for item in dic.history(None)? {
+        *lookups.entry(item.term).or_default() += 1;
+    }
+
+    let memos = items.iter().filter(|it| it.memo.is_some()).count();
+
+    println!("Notebook:");
+    println!("{}{:<17}{:>7}", INDENT, "Words", items.len().separated_string());
+    println!("{}{:<17}{:>7}", INDENT, "With memo", memos.separated_string());
+    println!();
+
+    let mut words: Vec<(&str, usize)> = items.iter()
+        .map(|it| (it.term.as_str(), lookups.get(&it.term).cloned().unwrap_or(0)))
+        .collect();
+    words.sort_by(|(a_word, a), (b_word, b)| b.cmp(a).then_with(|| a_word.cmp(b_word)));
+    println!("Lookups:");
+    for (word, count) in words {
+        println!("{}{:16} {:>7}", INDENT, word, count.separated_string());
+    }
+    println!();
+
+    Ok(())
+}
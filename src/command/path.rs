@@ -2,12 +2,18 @@
 use std::path::Path;
 
 use crate::errors::AppResultU;
-use crate::path::get_history_path;
+use crate::path::{get_config_path, get_history_path, get_user_dictionary_path};
 
 
-pub fn path<T: AsRef<Path>>(dictionary_path: &T) -> AppResultU {
+pub fn path<T: AsRef<Path>>(dictionary_paths: &[T]) -> AppResultU {
+    let config = get_config_path()?;
     let history = get_history_path()?;
-    println!("dictionary: {}", dictionary_path.as_ref().to_str().unwrap());
+    let user_dictionary = get_user_dictionary_path()?;
+    for dictionary_path in dictionary_paths {
+        println!("dictionary: {}", dictionary_path.as_ref().to_str().unwrap());
+    }
+    println!("config: {}", config.to_str().unwrap());
     println!("history: {}", history.to_str().unwrap());
+    println!("user dictionary: {}", user_dictionary.to_str().unwrap());
     Ok(())
 }
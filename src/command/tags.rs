@@ -0,0 +1,41 @@
+
+use std::path::Path;
+
+use structopt::StructOpt;
+
+use crate::dictionary::Dictionary;
+use crate::errors::{AppError, AppResultU};
+
+
+
+#[derive(Debug, StructOpt)]
+pub struct TagOpt {
+    /// Tag (e.g. 医)
+    tag: String,
+}
+
+
+pub fn tag<T: AsRef<Path>>(opt: TagOpt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
+    let terms = dic.tagged(&opt.tag)?;
+
+    if terms.is_empty() {
+        return Err(AppError::NotFound);
+    }
+
+    for term in terms {
+        println!("{}", term);
+    }
+
+    Ok(())
+}
+
+pub fn tags<T: AsRef<Path>>(dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
+
+    for (tag, count) in dic.tags()? {
+        println!("{}\t{}", tag, count);
+    }
+
+    Ok(())
+}
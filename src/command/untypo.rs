@@ -15,8 +15,8 @@ pub struct Opt {
 }
 
 
-pub fn untypo<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
+pub fn untypo<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
     for candidate in dic.correct(&opt.word) {
         println!("{}", candidate);
     }
@@ -0,0 +1,93 @@
+
+use std::env;
+use std::env::temp_dir;
+use std::fs::{read_to_string, remove_file, write};
+use std::process::{self, Command};
+
+use structopt::StructOpt;
+
+use crate::errors::{AppError, AppResultU};
+use crate::path::get_user_dictionary_path;
+use crate::user_dictionary::UserDictionary;
+
+
+
+const DEFAULT_EDITOR: &str = "vi";
+
+
+#[derive(Debug, StructOpt)]
+pub struct AddOpt {
+    /// Word
+    word: String,
+    /// Definition (Eijiro notation. e.g. "《名》社内用語")
+    definition: String,
+    /// Hide the definitions in the built dictionary
+    #[structopt(short, long)]
+    replace: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct EditOpt {
+    /// Word
+    word: String,
+    /// Hide the definitions in the built dictionary
+    #[structopt(short, long)]
+    replace: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct RemoveOpt {
+    /// Word
+    word: String,
+}
+
+
+pub fn add(opt: AddOpt) -> AppResultU {
+    let mut dic = UserDictionary::load()?;
+    dic.add(opt.word.trim(), opt.definition.trim(), opt.replace)?;
+    dic.save()
+}
+
+pub fn edit(opt: EditOpt) -> AppResultU {
+    let word = opt.word.trim();
+    let mut dic = UserDictionary::load()?;
+
+    let (current, replace) = dic.get(word).map(|it| (it.definitions.join("\n"), it.replace)).unwrap_or_default();
+
+    let path = temp_dir().join(format!("eitaro-{}.txt", process::id()));
+    write(&path, format!("# {}: One definition per line in Eijiro notation. Leave empty to remove.\n# User dictionary: {}\n{}\n", word, get_user_dictionary_path()?.display(), current))?;
+
+    let editor = env::var("EDITOR").unwrap_or_else(|_| DEFAULT_EDITOR.to_owned());
+    let mut editor = editor.split_whitespace();
+    let program = editor.next().unwrap_or(DEFAULT_EDITOR);
+    let status = Command::new(program).args(editor).arg(&path).status();
+    let edited = read_to_string(&path);
+    let _ = remove_file(&path);
+
+    if !status?.success() {
+        return Err(AppError::Eitaro("Editor exited with an error"));
+    }
+
+    let definitions: Vec<String> = edited?
+        .lines()
+        .map(str::trim)
+        .filter(|it| !it.is_empty() && !it.starts_with('#'))
+        .map(str::to_owned)
+        .collect();
+
+    if definitions.is_empty() {
+        dic.remove(word);
+    } else {
+        dic.set(word, definitions, replace || opt.replace)?;
+    }
+
+    dic.save()
+}
+
+pub fn remove(opt: RemoveOpt) -> AppResultU {
+    let mut dic = UserDictionary::load()?;
+    if !dic.remove(opt.word.trim()) {
+        return Err(AppError::NotFound);
+    }
+    dic.save()
+}
@@ -20,8 +20,8 @@ pub struct Opt {
     max: Option<u8>,
 }
 
-pub fn play<T: AsRef<Path>>(opt: Opt, dictionary_path: &T) -> AppResultU {
-    let dic = Dictionary::new(dictionary_path);
+pub fn play<T: AsRef<Path>>(opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
     let mut rng = thread_rng();
     let words = dic.wordle_words(opt.min.unwrap_or(0), opt.max.unwrap_or(100))?;
 
@@ -16,8 +16,8 @@ pub struct Opt {
 
 
 
-pub fn extract<T: AsRef<Path>>(_opt: Opt, dictionary_path: &T) -> AppResultU {
-    let mut dic = Dictionary::new(dictionary_path);
+pub fn extract<T: AsRef<Path>>(_opt: Opt, dictionary_paths: &[T]) -> AppResultU {
+    let dic = Dictionary::new(dictionary_paths);
 
     let mut text = "".to_owned();
     stdin().read_to_string(&mut text)?;
@@ -0,0 +1,137 @@
+
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::sync::Once;
+
+use serde_derive::Deserialize;
+
+use crate::errors::AppResult;
+use crate::path::get_config_path;
+use crate::theme::Theme;
+
+
+
+/// `config.toml` in the config directory (See `eitaro path`)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Default dictionary names in priority order (Overridden by `--dictionary`)
+    pub dictionaries: Vec<String>,
+    pub gui: Gui,
+    pub pager: Pager,
+    /// Shell prompt (Overridden by `--prompt` and `EITARO_PROMPT`)
+    pub prompt: Option<String>,
+    pub server: Server,
+    /// Color theme name (`dark`, `light` or one of `themes`)
+    pub theme: Option<String>,
+    /// User defined themes
+    pub themes: HashMap<String, Theme>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Gui {
+    pub font_name: Option<String>,
+    pub font_size: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Pager {
+    #[serde(default = "default_pager_command")]
+    pub command: String,
+    #[serde(default = "default_pager_args")]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Server {
+    /// "host:port" to listen
+    pub bind_to: Option<String>,
+    /// Ignore not found
+    pub ignore: bool,
+    /// Screen name (color, curses, gui or plain) used if no screen is given
+    pub screen: Option<String>,
+}
+
+
+static INIT: Once = Once::new();
+static mut CONFIG: Option<Config> = None;
+
+
+/// Loaded at the first call
+pub fn get() -> &'static Config {
+    unsafe {
+        INIT.call_once(|| {
+            let config = Config::load().unwrap_or_else(|error| {
+                eprintln!("Failed to load config: {}", error);
+                Config::default()
+            });
+            CONFIG = Some(config);
+        });
+        CONFIG.as_ref().expect("WTF: config")
+    }
+}
+
+
+impl Config {
+    fn load() -> AppResult<Self> {
+        let path = get_config_path()?;
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let source = read_to_string(path)?;
+        Ok(toml::from_str(&source)?)
+    }
+}
+
+impl Default for Pager {
+    fn default() -> Self {
+        Pager {
+            command: default_pager_command(),
+            args: default_pager_args(),
+        }
+    }
+}
+
+
+fn default_pager_command() -> String {
+    "less".to_owned()
+}
+
+/// For `less` (Set `args = []` for the other pagers)
+fn default_pager_args() -> Vec<String> {
+    vec!["--quit-if-one-screen".to_owned(), "--RAW-CONTROL-CHARS".to_owned(), "--no-init".to_owned()]
+}
+
+
+#[cfg(test)]#[test]
+fn test_parse() {
+    let config: Config = toml::from_str(r#"
+        dictionaries = ["eijiro", "ejdic"]
+        prompt = "> "
+
+        [pager]
+        command = "more"
+        args = []
+
+        [server]
+        bind_to = "0.0.0.0:8116"
+        screen = "plain"
+    "#).unwrap();
+
+    assert_eq!(config.dictionaries, vec!["eijiro", "ejdic"]);
+    assert_eq!(config.prompt, Some("> ".to_owned()));
+    assert_eq!(config.pager.command, "more");
+    assert!(config.pager.args.is_empty());
+    assert_eq!(config.server.bind_to, Some("0.0.0.0:8116".to_owned()));
+    assert!(!config.server.ignore);
+    assert_eq!(config.gui.font_size, None);
+
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.pager.command, "less");
+    assert_eq!(config.pager.args.len(), 3);
+
+    let config: Config = toml::from_str("[pager]\ncommand = \"less\"").unwrap();
+    assert_eq!(config.pager.args.len(), 3);
+}
@@ -0,0 +1,120 @@
+
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::result::QueryResult;
+use diesel::sql_types::{Integer, Nullable, Text};
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use diesel::RunQueryDsl;
+
+
+
+/// SQLITE_MAX_VARIABLE_NUMBER of the old SQLite
+const MAX_VARIABLES: usize = 999;
+
+
+/// Rows buffered for a multi-row `INSERT`
+/// The statement is prepared once for each number of rows, and cached by the connection
+pub struct BulkInsert {
+    columns: &'static [&'static str],
+    replace: bool,
+    rows: Vec<Vec<Value>>,
+    table: &'static str,
+}
+
+pub enum Value {
+    Integer(i32),
+    Text(Option<String>),
+}
+
+
+impl BulkInsert {
+    pub fn new(table: &'static str, columns: &'static [&'static str]) -> Self {
+        BulkInsert { columns, replace: false, rows: vec![], table }
+    }
+
+    /// `REPLACE INTO`
+    pub fn replace(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+
+    pub fn is_full(&self) -> bool {
+        MAX_VARIABLES < (self.rows.len() + 1) * self.columns.len()
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        assert_eq!(row.len(), self.columns.len(), "WTF: Number of columns");
+        self.rows.push(row);
+    }
+
+    /// Insert and clear the buffered rows
+    pub fn flush(&mut self, connection: &SqliteConnection) -> QueryResult<usize> {
+        if self.rows.is_empty() {
+            return Ok(0);
+        }
+        let result = RunQueryDsl::execute(&*self, connection);
+        self.rows.clear();
+        result
+    }
+}
+
+impl QueryFragment<Sqlite> for BulkInsert {
+    fn walk_ast(&self, mut out: AstPass<Sqlite>) -> QueryResult<()> {
+        out.push_sql(if self.replace { "REPLACE INTO " } else { "INSERT INTO " });
+        out.push_identifier(self.table)?;
+        out.push_sql(" (");
+        for (index, column) in self.columns.iter().enumerate() {
+            if 0 < index {
+                out.push_sql(", ");
+            }
+            out.push_identifier(column)?;
+        }
+        out.push_sql(") VALUES ");
+
+        for (index, row) in self.rows.iter().enumerate() {
+            out.push_sql(if 0 < index { ", (" } else { "(" });
+            for (index, value) in row.iter().enumerate() {
+                if 0 < index {
+                    out.push_sql(", ");
+                }
+                match value {
+                    Value::Integer(value) => out.push_bind_param::<Integer, _>(value)?,
+                    Value::Text(value) => out.push_bind_param::<Nullable<Text>, _>(value)?,
+                }
+            }
+            out.push_sql(")");
+        }
+
+        Ok(())
+    }
+}
+
+impl QueryId for BulkInsert {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<'a> RunQueryDsl<SqliteConnection> for &'a BulkInsert {}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(Some(value.to_owned()))
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(Some(value))
+    }
+}
+
+impl From<Option<String>> for Value {
+    fn from(value: Option<String>) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value)
+    }
+}
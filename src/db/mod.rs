@@ -1,4 +1,5 @@
 
+pub mod bulk;
 pub mod model;
 pub mod schema;
 
@@ -1,11 +1,18 @@
+
+use super::schema::definitions;
+
+
+
 #[derive(Queryable)]
 pub struct Alias {
     pub id: i32,
     pub key: String,
     pub target: String,
+    pub origin: Option<String>,
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, QueryableByName)]
+#[table_name = "definitions"]
 pub struct Definition {
     pub id: i32,
     pub term: String,
@@ -14,10 +21,66 @@ pub struct Definition {
     pub source: Option<String>,
 }
 
+#[derive(Queryable)]
+pub struct History {
+    pub id: i32,
+    pub term: String,
+    pub source: String,
+    pub found: bool,
+    pub created_at: String,
+}
+
 #[derive(Queryable)]
 pub struct Lemmatization {
     pub id: i32,
     pub source: String,
     pub target: String,
+    pub origin: Option<String>,
+}
+
+
+#[derive(Queryable)]
+pub struct Notebook {
+    pub term: String,
+    pub memo: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Queryable)]
+pub struct Review {
+    pub term: String,
+    pub easiness: f64,
+    pub interval_days: i32,
+    pub repetitions: i32,
+    pub due: i32,
+    pub reviewed_at: String,
+}
+
+#[derive(QueryableByName)]
+pub struct IndexSql {
+    #[sql_type = "diesel::sql_types::Text"]
+    pub name: String,
+    #[sql_type = "diesel::sql_types::Text"]
+    pub sql: String,
+}
+
+#[derive(QueryableByName)]
+pub struct SchemaVersion {
+    #[sql_type = "diesel::sql_types::Integer"]
+    pub user_version: i32,
 }
 
+#[derive(QueryableByName)]
+pub struct TagCount {
+    #[sql_type = "diesel::sql_types::Text"]
+    pub tag: String,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    pub count: i64,
+}
+
+#[derive(QueryableByName)]
+pub struct Term {
+    #[sql_type = "diesel::sql_types::Text"]
+    pub term: String,
+}
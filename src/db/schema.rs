@@ -3,6 +3,7 @@ table! {
         id -> Integer,
         source -> Text,
         target -> Text,
+        origin -> Nullable<Text>,
     }
 }
 
@@ -16,11 +17,22 @@ table! {
     }
 }
 
+table! {
+    history (id) {
+        id -> Integer,
+        term -> Text,
+        source -> Text,
+        found -> Bool,
+        created_at -> Text,
+    }
+}
+
 table! {
     lemmatizations (id) {
         id -> Integer,
         source -> Text,
         target -> Text,
+        origin -> Nullable<Text>,
     }
 }
 
@@ -29,6 +41,36 @@ table! {
         id -> Integer,
         term -> Text,
         level -> Integer,
+        origin -> Nullable<Text>,
+    }
+}
+
+table! {
+    notebook (term) {
+        term -> Text,
+        memo -> Nullable<Text>,
+        created_at -> Text,
+        updated_at -> Text,
+    }
+}
+
+table! {
+    reverse_index (id) {
+        id -> Integer,
+        fragment -> Text,
+        term -> Text,
+        source -> Nullable<Text>,
+    }
+}
+
+table! {
+    reviews (term) {
+        term -> Text,
+        easiness -> Double,
+        interval_days -> Integer,
+        repetitions -> Integer,
+        due -> Integer,
+        reviewed_at -> Text,
     }
 }
 
@@ -37,13 +79,18 @@ table! {
         id -> Integer,
         term -> Text,
         tag -> Text,
+        origin -> Nullable<Text>,
     }
 }
 
 allow_tables_to_appear_in_same_query!(
     aliases,
     definitions,
+    history,
     lemmatizations,
     levels,
+    notebook,
+    reverse_index,
+    reviews,
     tags,
 );
@@ -1,28 +1,61 @@
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::default::Default;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use array_tool::vec::Uniq;
-use diesel::connection::Connection;
+use diesel::connection::{Connection, SimpleConnection};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Error as ManagerError, Pool, PooledConnection};
 use diesel::sqlite::SqliteConnection;
 use if_let_return::if_let_some;
-use indexmap::indexset;
+use indexmap::{indexset, IndexMap, IndexSet};
 use lazy_init::Lazy;
 use regex::Regex;
 use serde_derive::{Serialize, Deserialize};
 
 use crate::correction::Corrector;
-use crate::db::model::{Definition as ModelDef};
+use crate::db::bulk::{BulkInsert, Value};
+use crate::db::model::{Definition as ModelDef, IndexSql, SchemaVersion, TagCount, Term};
 use crate::errors::{AppError, AppResult, AppResultU};
-use crate::str_utils::{fix_word, shorten, uncase};
+use crate::query::Query;
+use crate::str_utils::{self, fix_word, shorten, uncase};
+use crate::user_dictionary::UserDictionary;
 
 
 
+const DICTIONARY_TABLES: &[&str] = &[
+    "aliases",
+    "definitions",
+    "definitions_fts",
+    "lemmatizations",
+    "levels",
+    "reverse_index",
+    "tags",
+];
+/// Kept across rebuilds
+const USER_TABLE_NAMES: &[&str] = &["history", "notebook", "reviews"];
+/// Created on demand, not to require rebuilding
+const USER_TABLES: &[&str] = &[
+    include_str!("../migrations/2022-06-20-110245_create_history_and_notebook/up.sql"),
+    include_str!("../migrations/2022-06-24-140857_create_reviews/up.sql"),
+];
+/// Give up waiting for a connection (r2d2's default is 30 seconds)
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(3);
+/// Number of the migrations (Stored as `PRAGMA user_version`)
+const SCHEMA_VERSION: &str = env!("EITARO_SCHEMA_VERSION");
+
+
+type DbConnection = PooledConnection<ConnectionManager<SqliteConnection>>;
+
+
 pub struct Dictionary  {
     corrector: Lazy<AppResult<Corrector>>,
-    path: PathBuf,
-} 
+    filter: Filter,
+    /// In priority order. The first one is the primary (written by `build` and keeps the user data)
+    layers: Vec<Layer>,
+    user: Lazy<AppResult<UserDictionary>>,
+}
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub enum Text {
@@ -43,18 +76,38 @@ pub enum Text {
 pub struct Definition {
     pub key: String,
     pub content: Vec<Text>,
+    /// Source name given by `name@path` on build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct Entry {
     pub key: String,
     pub definitions: Vec<Definition>,
+    /// Dictionary name (Only if several dictionaries are stacked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<String>,
 }
 
-#[derive(Clone)]
+/// Rows are buffered and inserted in bulk. Call `flush` at the end
 pub struct DictionaryWriter<'a> {
+    aliases: BulkInsert,
     connection: &'a SqliteConnection,
-    source: Option<&'a str>,
+    definitions: BulkInsert,
+    lemmatizations: BulkInsert,
+    levels: BulkInsert,
+    reverse_index: BulkInsert,
+    source: Option<String>,
+    tags: BulkInsert,
+}
+
+/// Sources to include or exclude (Unnamed sources are never included by `include`), and tags to require
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub exclude: Vec<String>,
+    pub include: Vec<String>,
+    pub tags: Vec<String>,
 }
 
 pub struct Stat {
@@ -62,26 +115,53 @@ pub struct Stat {
     pub words: usize,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub enum WriteMode<'a> {
+    /// Drop all dictionary tables and build from scratch
+    Rebuild,
+    /// Keep the current entries and add new ones
+    Append,
+    /// Delete the entries of the given source before loading
+    Replace(&'a str),
+}
+
+#[derive(Debug)]
+struct ConnectionCustomizer {
+    /// Only the primary dictionary keeps the user data
+    primary: bool,
+}
+
+struct Layer {
+    name: String,
+    path: PathBuf,
+    pool: Pool<ConnectionManager<SqliteConnection>>,
+}
+
 
 
 impl Dictionary {
-    pub fn new<T: AsRef<Path>>(dictionary_path: &T) -> Self {
+    /// Dictionaries in priority order
+    pub fn new<T: AsRef<Path>>(dictionary_paths: &[T]) -> Self {
+        if dictionary_paths.is_empty() {
+            panic!("WTF: No dictionary");
+        }
+
         Dictionary {
             corrector: Lazy::new(),
-            path: dictionary_path.as_ref().to_path_buf()
+            filter: Filter::default(),
+            layers: dictionary_paths.iter().enumerate().map(|(index, it)| Layer::new(it.as_ref(), index == 0)).collect(),
+            user: Lazy::new(),
         }
     }
 
-    pub fn correct(&mut self, word: &str) -> Vec<String> {
-        println!("Correcting...");
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
 
+    pub fn correct(&self, word: &str) -> Vec<String> {
         let corrector = self.corrector.get_or_create(|| {
-            let connection = self.connect_db()?;
-            let keys = diesel_query!(definitions [Q R] {
-                d::definitions
-                    .select(d::term)
-                    .load::<String>(&connection)?
-            });
+            let keys = self.keys()?;
             Ok(Corrector { keys: keys.into_iter().collect() })
         });
 
@@ -96,12 +176,12 @@ impl Dictionary {
         }
     }
 
-    pub fn get_word<T: AsRef<Path>>(dictionary_path: &T, word: &str) -> Result<Option<Vec<Entry>>, AppError> {
-        let mut dic = Dictionary::new(dictionary_path);
-        Ok(dic.get_smart(&word)?)
-    }
+   pub fn get(&self, word: &str) -> AppResult<Option<Vec<Entry>>> {
+        self.get_with(word, &self.filter)
+   }
 
-   pub fn get(&mut self, word: &str) -> AppResult<Option<Vec<Entry>>> {
+   /// With the filter other than the dictionary's (e.g. given by each HTTP request)
+   pub fn get_with(&self, word: &str, filter: &Filter) -> AppResult<Option<Vec<Entry>>> {
         fn opt(result: Vec<Entry>) -> Option<Vec<Entry>> {
             if result.is_empty() {
                 return None;
@@ -109,31 +189,37 @@ impl Dictionary {
             Some(result)
         }
 
-        let connection = self.connect_db()?;
-
-        let mut candidates = indexset!(word.to_owned());
         let mut result = vec![];
 
-        for stemmed in stem(&word) {
-            candidates.insert(stemmed);
-        }
+        for (index, (name, connection)) in self.connect_all()?.into_iter().enumerate() {
+            let mut candidates = indexset!(word.to_owned());
 
-        if let Some(aliases) = lookup_unaliased(&connection, word)? {
-            for alias in aliases.split('\n') {
-                candidates.insert(alias.to_owned());
+            for stemmed in stem(&word) {
+                candidates.insert(stemmed);
             }
-        }
 
-        for candidate in &candidates {
-            if let Some(entry) = lookup_entry(&connection, candidate)? {
-                result.push(entry);
+            if let Some(aliases) = lookup_unaliased(&connection, word)? {
+                for alias in aliases.split('\n') {
+                    candidates.insert(alias.to_owned());
+                }
+            }
+
+            for candidate in &candidates {
+                let mut entry = lookup_entry(&connection, filter, candidate)?;
+                // The user dictionary is merged only once, into the primary
+                if index == 0 {
+                    entry = self.merge_user_entry(candidate, entry, filter)?;
+                }
+                if let Some(entry) = entry {
+                    result.push(labeled(entry, name));
+                }
             }
         }
 
         Ok(opt(result))
    }
 
-   pub fn get_level(&mut self, word: &str) -> AppResult<Option<u8>> {
+   pub fn get_level(&self, word: &str) -> AppResult<Option<u8>> {
        fn get_level(connection: &SqliteConnection, word: &str) -> AppResult<Option<u8>> {
            diesel_query!(levels [Q E R O] {
                let found = d::levels
@@ -146,22 +232,32 @@ impl Dictionary {
            })
        }
 
-       let connection = self.connect_db()?;
-
-       let found = get_level(&connection, word)?;
-       if found.is_some() {
-           return Ok(found)
+       for (_, connection) in self.connect_all()? {
+           let found = get_level(&connection, word)?;
+           if found.is_some() {
+               return Ok(found)
+           }
+
+           let lemmed = lemmatize(&connection, word)?;
+           let found = get_level(&connection, &lemmed)?;
+           if found.is_some() {
+               return Ok(found)
+           }
        }
 
-       let lemmed = lemmatize(&connection, word)?;
-       get_level(&connection, &lemmed)
+       Ok(None)
    }
 
-   pub fn get_smart(&mut self, word: &str) -> Result<Option<Vec<Entry>>, AppError> {
+   pub fn get_smart(&self, word: &str) -> Result<Option<Vec<Entry>>, AppError> {
+        self.get_smart_with(word, &self.filter)
+   }
+
+   /// The fallbacks continue until the entries passing `filter` are found
+   pub fn get_smart_with(&self, word: &str, filter: &Filter) -> Result<Option<Vec<Entry>>, AppError> {
         if_let_some!(fixed = fix_word(word), Ok(None));
 
         for shortened in shorten(&fixed) {
-            let mut result = self.get_similars(&shortened)?;
+            let mut result = self.get_similars(&shortened, filter)?;
             if let Some(result) = result.as_mut() {
                 return Ok(Some(result.unique()))
             }
@@ -169,7 +265,7 @@ impl Dictionary {
 
         let uncased = uncase(&word);
         if uncased != word {
-            if let Some(result) = self.get_smart(&uncased)? {
+            if let Some(result) = self.get_smart_with(&uncased, filter)? {
                 return Ok(Some(result))
             }
         }
@@ -178,7 +274,7 @@ impl Dictionary {
         let mut candidates: Vec<&str> = splitter.split(&fixed).collect();
         candidates.sort_by(|a, b| a.len().cmp(&b.len()).reverse());
         for candidate in candidates {
-            let result = self.get(candidate)?;
+            let result = self.get_with(candidate, filter)?;
             if result.is_some() {
                 return Ok(result);
             }
@@ -187,96 +283,316 @@ impl Dictionary {
         Ok(None)
     }
 
-    pub fn keys(&mut self) -> AppResult<Vec<String>> {
-        let connection = self.connect_db()?;
-        let keys = diesel_query!(definitions [Q R] {
-            d::definitions
-                .select(d::term)
-                .load::<String>(&connection)?
-        });
+    /// (alias, target)
+    pub fn aliases(&self) -> AppResult<Vec<(String, String)>> {
+        let mut result = vec![];
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!(aliases [Q R] {
+                d::aliases
+                    .select((d::source, d::target))
+                    .order(d::id)
+                    .load::<(String, String)>(&connection)?
+            });
+            result.extend(found);
+        }
+
+        Ok(result)
+    }
+
+    /// All entries ordered by the term
+    pub fn entries(&self) -> AppResult<Vec<Entry>> {
+        let mut result = vec![];
+        for (name, connection) in self.connect_all()? {
+            let found: Vec<ModelDef> = diesel_query!(definitions, Definition [Q R] {
+                d::definitions
+                    .order((d::term, d::id))
+                    .load::<Definition>(&connection)?
+            });
+            result.extend(compact_definitions(found, &self.filter)?.into_iter().map(|it| labeled(it, name)));
+        }
 
-        Ok(keys)
+        Ok(result)
     }
 
-    pub fn lemmatize(&mut self, word: &str) -> AppResult<String> {
-        let connection = self.connect_db()?;
-        lemmatize(&connection, word)
+    /// (inflected, lemma)
+    pub fn inflections(&self) -> AppResult<Vec<(String, String)>> {
+        let mut result = vec![];
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!(lemmatizations [Q R] {
+                d::lemmatizations
+                    .select((d::source, d::target))
+                    .order(d::id)
+                    .load::<(String, String)>(&connection)?
+            });
+            result.extend(found);
+        }
+
+        Ok(result)
+    }
+
+    pub fn keys(&self) -> AppResult<Vec<String>> {
+        let mut result = vec![];
+        for (_, connection) in self.connect_all()? {
+            let keys = diesel_query!(definitions [Q R] {
+                d::definitions
+                    .select(d::term)
+                    .load::<String>(&connection)?
+            });
+            result.extend(keys);
+        }
+
+        Ok(result)
+    }
+
+    pub fn levels(&self) -> AppResult<Vec<(String, u8)>> {
+        let mut result = vec![];
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!(levels [Q R] {
+                d::levels
+                    .select((d::term, d::level))
+                    .load::<(String, i32)>(&connection)?
+            });
+            result.extend(found.into_iter().map(|(term, level)| (term, level as u8)));
+        }
+
+        Ok(result)
+    }
+
+    pub fn lemmatize(&self, word: &str) -> AppResult<String> {
+        for (_, connection) in self.connect_all()? {
+            let lemmed = lemmatize(&connection, word)?;
+            if lemmed != word {
+                return Ok(lemmed);
+            }
+        }
+        Ok(word.to_owned())
     }
 
     pub fn like(&self, query: &str) -> AppResult<Option<Vec<Entry>>> {
-        let connection = self.connect_db()?;
+        self.like_with(query, &self.filter)
+    }
 
-        let found: Vec<ModelDef> = diesel_query!(definitions, Definition [Q R T] {
-            d::definitions.
-                filter(d::term.like(query))
-                .order((d::term, d::id))
-                .load::<Definition>(&connection)?
-        });
+    pub fn like_with(&self, query: &str, filter: &Filter) -> AppResult<Option<Vec<Entry>>> {
+        let mut result = vec![];
+
+        for (name, connection) in self.connect_all()? {
+            let found: Vec<ModelDef> = diesel_query!(definitions, Definition [Q R T] {
+                d::definitions.
+                    filter(d::term.like(query))
+                    .order((d::term, d::id))
+                    .load::<Definition>(&connection)?
+            });
+            result.extend(compact_definitions(found, filter)?.into_iter().map(|it| labeled(it, name)));
+        }
 
-        if found.is_empty() {
+        if result.is_empty() {
             return Ok(None)
         }
 
-        Ok(Some(compact_definitions(found)?))
+        Ok(Some(result))
+    }
+
+    /// Japanese to English
+    pub fn reverse_lookup(&self, query: &str, limit: usize) -> AppResult<Option<Vec<Entry>>> {
+        self.reverse_lookup_with(query, limit, &self.filter)
+    }
+
+    pub fn reverse_lookup_with(&self, query: &str, limit: usize, filter: &Filter) -> AppResult<Option<Vec<Entry>>> {
+        if_let_some!(query = str_utils::normalize_fragment(query), Ok(None));
+
+        let mut result = vec![];
+
+        for (name, connection) in self.connect_all()? {
+            if limit <= result.len() {
+                break;
+            }
+
+            // Exact and prefix matches use the index, and the substring scan is the fallback
+            let mut terms = vec![];
+            for condition in &["fragment >= ?1 AND fragment < ?1 || x'ff'", "fragment LIKE '%' || ?1 || '%'"] {
+                terms = diesel_query!([R] {
+                    use diesel::sql_types::{BigInt, Text};
+
+                    diesel::sql_query(format!(
+                        "SELECT term FROM reverse_index \
+                         WHERE {} \
+                         GROUP BY term \
+                         ORDER BY MIN(CASE WHEN fragment = ?1 THEN 0 WHEN substr(fragment, 1, length(?1)) = ?1 THEN 1 ELSE 2 END), \
+                                  MIN(length(fragment)), \
+                                  length(term), \
+                                  term \
+                         LIMIT ?2",
+                        condition))
+                        .bind::<Text, _>(&query)
+                        .bind::<BigInt, _>((limit - result.len()) as i64)
+                        .load::<Term>(&connection)?
+                });
+                if !terms.is_empty() {
+                    break;
+                }
+            }
+
+            for term in terms {
+                if let Some(entry) = lookup_entry(&connection, filter, &term.term)? {
+                    result.push(labeled(entry, name));
+                }
+            }
+        }
+
+        if result.is_empty() {
+            return Ok(None)
+        }
+
+        Ok(Some(result))
     }
 
     pub fn search(&self, query: &str) -> AppResult<Option<Vec<Entry>>> {
-        let connection = self.connect_db()?;
+        self.search_with(query, &self.filter)
+    }
 
-        let found = diesel_query!(definitions, Definition [B E Q R T] {
-            use diesel::BoxableExpression;
-            use diesel::sql_types::Bool;
+    pub fn search_with(&self, query: &str, filter: &Filter) -> AppResult<Option<Vec<Entry>>> {
+        let query = Query::parse(query);
+        let mut result = vec![];
 
-            let truee = Box::new(d::term.eq(d::term));
-            let q: Box<dyn BoxableExpression<d::definitions, _, SqlType = Bool>> =
-                query.split_ascii_whitespace()
-                .map(|it| d::text.like(format!("%{}%", it)))
-                .fold(truee, |q, it| Box::new(q.and(it)));
+        for (name, connection) in self.connect_all()? {
+            let found = search(&connection, &query)?;
+            result.extend(compact_definitions(found, filter)?.into_iter().map(|it| labeled(it, name)));
+        }
 
-            d::definitions.
-                filter(q)
-                .order((d::term, d::id))
-                .load::<Definition>(&connection)?
-        });
+        if result.is_empty() {
+            return Ok(None)
+        }
 
-        Ok(Some(compact_definitions(found)?))
+        Ok(Some(result))
     }
 
-    pub fn write<F>(&mut self, mut f: F) -> AppResult<Stat> where F: FnMut(&mut DictionaryWriter) -> AppResultU {
-        if self.path.exists() {
-            std::fs::remove_file(&self.path)?;
+    pub fn write<F>(&mut self, mode: WriteMode, mut f: F) -> AppResult<Stat> where F: FnMut(&mut DictionaryWriter) -> AppResultU {
+        let connection = self.establish()?;
+
+        let rebuild = match mode {
+            WriteMode::Rebuild => true,
+            _ => !has_table(&connection, "definitions")?,
+        };
+
+        if !rebuild && schema_version(&connection)? < SCHEMA_VERSION.parse::<i32>().unwrap() {
+            return Err(AppError::Eitaro("Rebuild required: The dictionary was built by an older version (Run `build` without `--append` and `--replace-source`)"));
         }
 
-        let connection = self.connect_db()?;
+        let mut indexes = vec![];
 
-        diesel_query!([R] {
-            for sql in include_str!("../migrations.sql").split(';') {
-                diesel::sql_query(sql).execute(&connection)?;
+        if rebuild {
+            // Without the journal, a crash can corrupt the whole file. So skip it only when no user data is in the file
+            if !has_user_data(&connection)? {
+                connection.batch_execute("PRAGMA journal_mode = OFF; PRAGMA synchronous = OFF")?;
             }
-        });
+            diesel_query!([R] {
+                // Drop only the dictionary tables to keep the user data (history, notebook)
+                for table in DICTIONARY_TABLES {
+                    diesel::sql_query(format!("DROP TABLE IF EXISTS {}", table)).execute(&connection)?;
+                }
+                let fts = supports_trigram(&sqlite_version(&connection)?);
+                for sql in include_str!("../migrations.sql").split(';') {
+                    if !fts && sql.contains("USING fts5") {
+                        continue;
+                    }
+                    diesel::sql_query(sql).execute(&connection)?;
+                }
+            });
+            // Indexing after loading is much faster than updating the indexes on each insert
+            indexes = drop_indexes(&connection)?;
+        }
 
-        connection.transaction::<_, AppError, _>(|| {
-            use crate::db::schema;
-            use diesel::RunQueryDsl;
+        let stat = connection.transaction::<_, AppError, _>(|| {
+            let fts = has_fts(&connection)?;
 
-            diesel::delete(schema::aliases::dsl::aliases).execute(&connection)?;
-            diesel::delete(schema::definitions::dsl::definitions).execute(&connection)?;
-            diesel::delete(schema::lemmatizations::dsl::lemmatizations).execute(&connection)?;
-            diesel::delete(schema::levels::dsl::levels).execute(&connection)?;
+            if let WriteMode::Replace(source) = mode {
+                if fts {
+                    delete_fts_source(&connection, source)?;
+                }
+                delete_source(&connection, source)?;
+            }
 
-            let mut writer = DictionaryWriter::new(&connection, None);
+            let last_id = last_definition_id(&connection)?;
+            let mut writer = DictionaryWriter::new(&connection);
             f(&mut writer)?;
+            writer.flush()?;
+
+            for index in &indexes {
+                connection.batch_execute(&index.sql)?;
+            }
+
+            if fts {
+                // Index only the added definitions unless rebuilding
+                diesel_query!([R] {
+                    if rebuild {
+                        diesel::sql_query("INSERT INTO definitions_fts(definitions_fts) VALUES('rebuild')").execute(&connection)?;
+                    } else {
+                        diesel::sql_query("INSERT INTO definitions_fts(rowid, term, text) SELECT id, term, text FROM definitions WHERE ? < id")
+                            .bind::<diesel::sql_types::Integer, _>(last_id)
+                            .execute(&connection)?;
+                    }
+                });
+            }
+
             stat(&connection)
-        })
+        })?;
+
+        connection.batch_execute("ANALYZE")?;
+        if rebuild {
+            connection.batch_execute("VACUUM")?;
+        }
+
+        Ok(stat)
+    }
+
+    /// Connection to the primary dictionary
+    pub(crate) fn connect_db(&self) -> AppResult<DbConnection> {
+        Ok(self.layers[0].pool.get()?)
+    }
+
+    /// Connections in priority order, with the dictionary names if stacked
+    fn connect_all(&self) -> AppResult<Vec<(Option<&str>, DbConnection)>> {
+        let stacked = 1 < self.layers.len();
+        let mut result = vec![];
+        for layer in &self.layers {
+            let name = if stacked { Some(layer.name.as_str()) } else { None };
+            result.push((name, layer.pool.get()?));
+        }
+        Ok(result)
     }
 
-    fn connect_db(&self) -> AppResult<SqliteConnection> {
-        let path = self.path.to_str().ok_or(AppError::Unexpect("WTF: connection"))?;
+    fn establish(&self) -> AppResult<SqliteConnection> {
+        let path = self.layers[0].path.to_str().ok_or(AppError::Unexpect("WTF: connection"))?;
         Ok(SqliteConnection::establish(path)?)
     }
 
-    fn get_similars(&mut self, word: &str) -> AppResult<Option<Vec<Entry>>> {
-        let mut result = self.get(word)?;
+    /// User definitions come first
+    fn merge_user_entry(&self, word: &str, entry: Option<Entry>, filter: &Filter) -> AppResult<Option<Entry>> {
+        let user = match self.user.get_or_create(UserDictionary::load) {
+            Ok(user) => user,
+            Err(error) => {
+                eprintln!("{}", error);
+                return Ok(entry);
+            }
+        };
+        if_let_some!(user_entry = user.get(word), Ok(entry));
+
+        let mut definitions: Vec<Definition> = user_entry.to_definitions()?.into_iter().filter(|it| filter.accepts(it)).collect();
+        if !user_entry.replace {
+            if let Some(entry) = entry {
+                definitions.extend(entry.definitions);
+            }
+        }
+
+        if definitions.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Entry { key: word.to_owned(), definitions, dictionary: None }))
+    }
+
+    fn get_similars(&self, word: &str, filter: &Filter) -> AppResult<Option<Vec<Entry>>> {
+        let mut result = self.get_with(word, filter)?;
 
         {
             let mut mutated = vec![];
@@ -285,7 +601,7 @@ impl Dictionary {
                 for to in &["-", " ", ""] {
                     let replaced = word.replace(*from, to);
                     if replaced != word {
-                        if let Some(result) = self.get(&replaced)? {
+                        if let Some(result) = self.get_with(&replaced, filter)? {
                             mutated.extend_from_slice(&result);
                         }
                     }
@@ -301,52 +617,117 @@ impl Dictionary {
             }
         }
 
+        // Keep the entries of each dictionary together
+        if let Some(result) = result.as_mut() {
+            result.sort_by_key(|it| self.layers.iter().position(|layer| Some(&layer.name) == it.dictionary.as_ref()));
+        }
+
         Ok(result)
     }
 
-    pub fn wordle_words(&self, min: u8, max: u8) -> AppResult<Vec<String>> {
-        let connection = self.connect_db()?;
-
-        let found = diesel_query!(levels [E Q R T] {
-            d::levels
-                .filter(d::level.ge(i32::from(min)))
-                .filter(d::level.le(i32::from(max)))
-                .filter(d::term.like("_____"))
-                .select(d::term)
-                .load::<String>(&connection)?
-        });
+    pub fn level_words(&self, min: u8, max: u8) -> AppResult<Vec<String>> {
+        let mut result = IndexSet::new();
+
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!(levels [E Q R] {
+                d::levels
+                    .filter(d::level.ge(i32::from(min)))
+                    .filter(d::level.le(i32::from(max)))
+                    .select(d::term)
+                    .load::<String>(&connection)?
+            });
+            result.extend(found);
+        }
 
-        Ok(found)
+        Ok(result.into_iter().collect())
     }
-}
 
+    /// Terms having the tag
+    pub fn tagged(&self, tag: &str) -> AppResult<Vec<String>> {
+        let mut result = BTreeSet::new();
+
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!(tags [E Q R] {
+                d::tags
+                    .filter(d::tag.eq(tag))
+                    .select(d::term)
+                    .distinct()
+                    .load::<String>(&connection)?
+            });
+            result.extend(found);
+        }
 
-fn compact_definitions(defs: Vec<ModelDef>) -> AppResult<Vec<Entry>> {
-    let defs: serde_json::Result<Vec<(String, Definition)>> =
-        defs.into_iter().map(|it| serde_json::from_str::<Definition>(&it.definition).map(|d| (it.term, d))).collect();
-    let defs = defs?;
+        Ok(result.into_iter().collect())
+    }
 
-    let mut result = vec![];
-    let mut buffer = vec![];
-    let mut last_key = defs[0].0.clone();
+    /// Tags and the number of terms (summed over the stacked dictionaries)
+    pub fn tags(&self) -> AppResult<Vec<(String, usize)>> {
+        let mut counts = IndexMap::<String, usize>::new();
 
-    for (key, def) in defs {
-        if key == last_key {
-            buffer.push(def);
-        } else {
-            let mut definitions = vec![def];
-            let mut key = key;
-            std::mem::swap(&mut buffer, &mut definitions);
-            std::mem::swap(&mut key, &mut last_key);
-            result.push(Entry { key, definitions });
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!([R] {
+                diesel::sql_query(
+                    "SELECT tag, COUNT(DISTINCT term) AS count FROM tags \
+                     GROUP BY tag")
+                    .load::<TagCount>(&connection)?
+            });
+            for it in found {
+                *counts.entry(it.tag).or_insert(0) += it.count as usize;
+            }
         }
+
+        let mut result: Vec<(String, usize)> = counts.into_iter().collect();
+        result.sort_by(|(a_tag, a_count), (b_tag, b_count)| b_count.cmp(a_count).then_with(|| a_tag.cmp(b_tag)));
+        Ok(result)
     }
 
-    if !buffer.is_empty() {
-        result.push(Entry { key: last_key, definitions: buffer });
+    pub fn wordle_words(&self, min: u8, max: u8) -> AppResult<Vec<String>> {
+        let mut result = IndexSet::new();
+
+        for (_, connection) in self.connect_all()? {
+            let found = diesel_query!(levels [E Q R T] {
+                d::levels
+                    .filter(d::level.ge(i32::from(min)))
+                    .filter(d::level.le(i32::from(max)))
+                    .filter(d::term.like("_____"))
+                    .select(d::term)
+                    .load::<String>(&connection)?
+            });
+            result.extend(found);
+        }
+
+        Ok(result.into_iter().collect())
     }
+}
 
-    Ok(result)
+
+/// Dictionary name to show before the entry at the index (Only at the first entry of each dictionary)
+pub fn dictionary_header(entries: &[Entry], index: usize) -> Option<&str> {
+    let name = entries[index].dictionary.as_ref().map(String::as_str)?;
+    if 0 < index && entries[index - 1].dictionary.as_ref().map(String::as_str) == Some(name) {
+        return None;
+    }
+    Some(name)
+}
+
+
+fn labeled(mut entry: Entry, dictionary: Option<&str>) -> Entry {
+    entry.dictionary = dictionary.map(str::to_owned);
+    entry
+}
+
+fn compact_definitions(defs: Vec<ModelDef>, filter: &Filter) -> AppResult<Vec<Entry>> {
+    let mut entries = IndexMap::<String, Vec<Definition>>::new();
+
+    for def in defs {
+        let definition = Definition::from_model(&def)?;
+        if !filter.accepts(&definition) {
+            continue;
+        }
+        entries.entry(def.term).or_insert_with(Vec::new).push(definition);
+    }
+
+    Ok(entries.into_iter().map(|(key, definitions)| Entry { key, definitions, dictionary: None }).collect())
 }
 
 fn lemmatize(connection: &SqliteConnection, word: &str) -> AppResult<String> {
@@ -360,12 +741,12 @@ fn lemmatize(connection: &SqliteConnection, word: &str) -> AppResult<String> {
         lemmed = found;
     }
 
-    if lookup_entry(connection, &lemmed)?.is_some() {
+    if lookup_entry(connection, &Filter::default(), &lemmed)?.is_some() {
         return Ok(lemmed.to_owned());
     }
 
     for stemmed in stem(&lemmed) {
-        if lookup_entry(connection, &stemmed)?.is_some() {
+        if lookup_entry(connection, &Filter::default(), &stemmed)?.is_some() {
             return Ok(stemmed);
         }
     }
@@ -373,22 +754,29 @@ fn lemmatize(connection: &SqliteConnection, word: &str) -> AppResult<String> {
     Ok(lemmed.to_owned())
 }
 
-fn lookup_entry(connection: &SqliteConnection, word: &str) -> AppResult<Option<Entry>> {
+fn lookup_entry(connection: &SqliteConnection, filter: &Filter, word: &str) -> AppResult<Option<Entry>> {
     let found = diesel_query!(definitions, Definition [Q E R] {
         d::definitions
             .filter(d::term.eq(word))
             .load::<Definition>(connection)?
     });
 
-    if found.is_empty() {
-        return Ok(None)
+    let mut defs = vec![];
+    for it in &found {
+        let definition = Definition::from_model(it)?;
+        if filter.accepts(&definition) {
+            defs.push(definition);
+        }
     }
 
-    let defs: serde_json::Result<Vec<Definition>> = found.iter().map(|it| serde_json::from_str::<Definition>(&it.definition)).collect();
+    if defs.is_empty() {
+        return Ok(None)
+    }
 
     Ok(Some(Entry {
         key: word.to_owned(),
-        definitions: defs?,
+        definitions: defs,
+        dictionary: None,
     }))
 }
 
@@ -414,6 +802,174 @@ fn lookup_unaliased(connection: &SqliteConnection, word: &str) -> AppResult<Opti
     })
 }
 
+fn search(connection: &SqliteConnection, query: &Query) -> AppResult<Vec<ModelDef>> {
+    let fts = if has_fts(connection)? { query.to_fts() } else { None };
+    let found = if let Some(fts) = fts {
+        diesel_query!([R] {
+            use diesel::sql_types::Text;
+
+            diesel::sql_query(
+                "SELECT definitions.* FROM definitions_fts \
+                 JOIN definitions ON definitions.id = definitions_fts.rowid \
+                 WHERE definitions_fts MATCH ? \
+                 ORDER BY bm25(definitions_fts), definitions.id")
+                .bind::<Text, _>(fts)
+                .load::<ModelDef>(connection)?
+                .into_iter()
+                // Keep the ranking by the long terms
+                .filter(|it| query.matches_short_terms(&it.text))
+                .collect::<Vec<_>>()
+        })
+    } else {
+        // Fallback for the queries without any long term (or SQLite without the trigram tokenizer)
+        diesel_query!(definitions, Definition [B E Q R T] {
+            use diesel::BoxableExpression;
+            use diesel::sql_types::Bool;
+
+            let truee = Box::new(d::term.eq(d::term));
+            let q: Box<dyn BoxableExpression<d::definitions, _, SqlType = Bool>> =
+                query.terms.iter()
+                .fold(truee, |q, it| {
+                    let pattern = format!("%{}%", it.text);
+                    if it.negative {
+                        Box::new(q.and(d::text.not_like(pattern)))
+                    } else {
+                        Box::new(q.and(d::text.like(pattern)))
+                    }
+                });
+
+            d::definitions.
+                filter(q)
+                .order((d::term, d::id))
+                .load::<Definition>(connection)?
+        })
+    };
+
+    Ok(found)
+}
+
+fn delete_source(connection: &SqliteConnection, source: &str) -> AppResultU {
+    diesel_query!(aliases [E Q R] {
+        diesel::delete(d::aliases.filter(d::origin.eq(source))).execute(connection)?;
+    });
+    diesel_query!(definitions [E Q R] {
+        diesel::delete(d::definitions.filter(d::source.eq(source))).execute(connection)?;
+    });
+    diesel_query!(lemmatizations [E Q R] {
+        diesel::delete(d::lemmatizations.filter(d::origin.eq(source))).execute(connection)?;
+    });
+    diesel_query!(levels [E Q R] {
+        diesel::delete(d::levels.filter(d::origin.eq(source))).execute(connection)?;
+    });
+    diesel_query!(reverse_index [E Q R] {
+        diesel::delete(d::reverse_index.filter(d::source.eq(source))).execute(connection)?;
+    });
+    diesel_query!(tags [E Q R] {
+        diesel::delete(d::tags.filter(d::origin.eq(source))).execute(connection)?;
+    });
+    Ok(())
+}
+
+/// Must be called before the definitions are deleted (The index has no copy of the content)
+fn delete_fts_source(connection: &SqliteConnection, source: &str) -> AppResultU {
+    diesel_query!([R] {
+        diesel::sql_query(
+            "INSERT INTO definitions_fts(definitions_fts, rowid, term, text) \
+             SELECT 'delete', id, term, text FROM definitions WHERE source = ?")
+            .bind::<diesel::sql_types::Text, _>(source)
+            .execute(connection)?;
+    });
+    Ok(())
+}
+
+/// The definitions inserted after this have greater IDs
+fn last_definition_id(connection: &SqliteConnection) -> AppResult<i32> {
+    diesel_query!(definitions [Q R] {
+        let found = d::definitions.select(diesel::dsl::max(d::id)).first::<Option<i32>>(connection)?;
+        Ok(found.unwrap_or(0))
+    })
+}
+
+/// Returns the dropped indexes to create them again
+fn drop_indexes(connection: &SqliteConnection) -> AppResult<Vec<IndexSql>> {
+    let mut result = vec![];
+    for table in DICTIONARY_TABLES {
+        let found = diesel_query!([R] {
+            diesel::sql_query("SELECT name, sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ? AND sql IS NOT NULL")
+                .bind::<diesel::sql_types::Text, _>(table)
+                .load::<IndexSql>(connection)?
+        });
+        result.extend(found);
+    }
+
+    for index in &result {
+        connection.batch_execute(&format!("DROP INDEX {}", index.name))?;
+    }
+
+    Ok(result)
+}
+
+fn has_table(connection: &SqliteConnection, name: &str) -> AppResult<bool> {
+    let found = diesel_query!([R] {
+        diesel::sql_query("SELECT name AS term FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind::<diesel::sql_types::Text, _>(name)
+            .load::<Term>(connection)?
+    });
+    Ok(!found.is_empty())
+}
+
+/// The trigram index is available (Built and usable by this SQLite)
+fn has_fts(connection: &SqliteConnection) -> AppResult<bool> {
+    Ok(supports_trigram(&sqlite_version(connection)?) && has_table(connection, "definitions_fts")?)
+}
+
+fn sqlite_version(connection: &SqliteConnection) -> AppResult<String> {
+    let found = diesel_query!([R] {
+        diesel::sql_query("SELECT sqlite_version() AS term").load::<Term>(connection)?
+    });
+    Ok(found.into_iter().next().map(|it| it.term).unwrap_or_default())
+}
+
+/// The trigram tokenizer is available since SQLite 3.34.0
+fn supports_trigram(version: &str) -> bool {
+    let mut numbers = version.split('.').map(|it| it.parse::<u32>().unwrap_or(0));
+    let major = numbers.next().unwrap_or(0);
+    let minor = numbers.next().unwrap_or(0);
+    (3, 34) <= (major, minor)
+}
+
+/// History, notebook or reviews has any row
+fn has_user_data(connection: &SqliteConnection) -> AppResult<bool> {
+    for table in USER_TABLE_NAMES {
+        if !has_table(connection, table)? {
+            continue;
+        }
+        let found = diesel_query!([R] {
+            diesel::sql_query(format!("SELECT 'found' AS term FROM {} LIMIT 1", table)).load::<Term>(connection)?
+        });
+        if !found.is_empty() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn insert(connection: &SqliteConnection, bulk: &mut BulkInsert, row: Vec<Value>) -> AppResultU {
+    if bulk.is_full() {
+        bulk.flush(connection)?;
+    }
+    bulk.push(row);
+    Ok(())
+}
+
+fn schema_version(connection: &SqliteConnection) -> AppResult<i32> {
+    let found = diesel_query!([R] {
+        diesel::sql_query("PRAGMA user_version")
+            .load::<SchemaVersion>(connection)?
+    });
+    Ok(found.get(0).map(|it| it.user_version).unwrap_or(0))
+}
+
 fn stat(connection: &SqliteConnection) -> AppResult<Stat> {
     // FIXME
     let words = diesel_query!(definitions [Q R] {
@@ -468,18 +1024,22 @@ fn stem(word: &str) -> Vec<String> {
 
 
 impl<'a> DictionaryWriter<'a> {
-    fn new(connection: &'a SqliteConnection, source: Option<&'a str>) -> Self {
+    fn new(connection: &'a SqliteConnection) -> Self {
         DictionaryWriter {
+            aliases: BulkInsert::new("aliases", &["source", "target", "origin"]),
             connection,
-            source
+            definitions: BulkInsert::new("definitions", &["term", "definition", "text", "source"]),
+            lemmatizations: BulkInsert::new("lemmatizations", &["source", "target", "origin"]),
+            levels: BulkInsert::new("levels", &["term", "level", "origin"]).replace(),
+            reverse_index: BulkInsert::new("reverse_index", &["fragment", "term", "source"]),
+            source: None,
+            tags: BulkInsert::new("tags", &["term", "tag", "origin"]),
         }
     }
 
-    pub fn with_source(self, source: Option<&'a str>) -> Self {
-        DictionaryWriter {
-            connection: self.connection,
-            source,
-        }
+    /// Source name of the following rows
+    pub fn set_source(&mut self, source: Option<&str>) {
+        self.source = source.map(str::to_owned);
     }
 
     pub fn alias(&mut self, from: &str, to: &str, for_lemmatization: bool) -> AppResultU {
@@ -489,18 +1049,12 @@ impl<'a> DictionaryWriter<'a> {
             }
 
             if for_lemmatization {
-                diesel_query!(lemmatizations [E R] {
-                    diesel::insert_into(d::lemmatizations)
-                        .values((d::source.eq(&from), d::target.eq(&to)))
-                        .execute(self.connection)?;
-                });
+                let row = vec![from.as_str().into(), to.as_str().into(), self.source.clone().into()];
+                insert(self.connection, &mut self.lemmatizations, row)?;
             }
 
-            diesel_query!(aliases [E R] {
-                diesel::insert_into(d::aliases)
-                    .values((d::source.eq(&from), d::target.eq(&to)))
-                    .execute(self.connection)?;
-            });
+            let row = vec![from.into(), to.into(), self.source.clone().into()];
+            insert(self.connection, &mut self.aliases, row)?;
         }
         Ok(())
     }
@@ -518,33 +1072,58 @@ impl<'a> DictionaryWriter<'a> {
             }
         }
 
-        let def = Definition { key: key.to_owned(), content };
+        let mut fragments = vec![];
+        for it in &content {
+            if let Text::Definition(s) = it {
+                fragments.extend(str_utils::fragments(s));
+            }
+        }
 
-        diesel_query!(definitions [E R] {
-            let serialized = serde_json::to_string(&def).unwrap();
-            diesel::insert_into(d::definitions)
-                .values((d::term.eq(lkey), d::definition.eq(serialized), d::text.eq(&buffer), d::source.eq(self.source)))
-                .execute(self.connection)?;
-        });
+        let def = Definition { key: key.to_owned(), content, source: None };
+        let serialized = serde_json::to_string(&def).unwrap();
+
+        let row = vec![lkey.as_str().into(), serialized.into(), buffer.into(), self.source.clone().into()];
+        insert(self.connection, &mut self.definitions, row)?;
+
+        for fragment in fragments {
+            let row = vec![fragment.into(), lkey.as_str().into(), self.source.clone().into()];
+            insert(self.connection, &mut self.reverse_index, row)?;
+        }
 
         Ok(())
     }
 
     pub fn tag(&mut self, term: &str, tag: &str) -> AppResultU {
-        diesel_query!(tags [E R] {
-            diesel::insert_into(d::tags)
-                .values((d::term.eq(&term), d::tag.eq(&tag)))
-                .execute(self.connection)?;
-        });
-        Ok(())
+        let row = vec![term.to_lowercase().into(), tag.into(), self.source.clone().into()];
+        insert(self.connection, &mut self.tags, row)
     }
 
     pub fn levelize(&mut self, level: u8, key: &str) -> AppResultU {
-        diesel_query!(levels [E R] {
-            diesel::replace_into(d::levels)
-                .values((d::term.eq(&key), d::level.eq(i32::from(level))))
-                .execute(self.connection)?;
-        });
+        let row = vec![key.into(), i32::from(level).into(), self.source.clone().into()];
+        insert(self.connection, &mut self.levels, row)
+    }
+
+    /// Insert the buffered rows
+    pub fn flush(&mut self) -> AppResultU {
+        for bulk in &mut [&mut self.aliases, &mut self.definitions, &mut self.lemmatizations, &mut self.levels, &mut self.reverse_index, &mut self.tags] {
+            bulk.flush(self.connection)?;
+        }
+        Ok(())
+    }
+}
+
+
+
+impl CustomizeConnection<SqliteConnection, ManagerError> for ConnectionCustomizer {
+    fn on_acquire(&self, connection: &mut SqliteConnection) -> Result<(), ManagerError> {
+        // Some threads (e.g. HTTP server workers) write history at the same time
+        connection.batch_execute("PRAGMA busy_timeout = 3000").map_err(ManagerError::QueryError)?;
+        if self.primary {
+            // Dictionaries built before the user tables were added do not have them
+            for sql in USER_TABLES {
+                connection.batch_execute(sql).map_err(ManagerError::QueryError)?;
+            }
+        }
         Ok(())
     }
 }
@@ -554,9 +1133,63 @@ impl<'a> DictionaryWriter<'a> {
 // TODO REMOVE ME
 impl Default for Definition {
     fn default() -> Self {
-        Definition { key: "dummy-key".to_owned(), content: vec![Text::Note("dummy-content".to_owned())] }
+        Definition { key: "dummy-key".to_owned(), content: vec![Text::Note("dummy-content".to_owned())], source: None }
+    }
+
+}
+
+impl Definition {
+    fn from_model(def: &ModelDef) -> AppResult<Self> {
+        let mut definition = serde_json::from_str::<Definition>(&def.definition)?;
+        definition.source = def.source.clone();
+        Ok(definition)
     }
+}
 
+impl Layer {
+    fn new(path: &Path, primary: bool) -> Self {
+        // Do not open any connection until the first query, the dictionary may not be built yet
+        let pool = Pool::builder()
+            .min_idle(Some(0))
+            .connection_timeout(CONNECTION_TIMEOUT)
+            .connection_customizer(Box::new(ConnectionCustomizer { primary }))
+            .build_unchecked(ConnectionManager::new(path.to_string_lossy()));
+        // `eijiro.sqlite` => `eijiro`
+        let name = path.file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
+
+        Layer { name, path: path.to_path_buf(), pool }
+    }
+}
+
+impl Filter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Filter { exclude, include, tags: vec![] }
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn accepts(&self, definition: &Definition) -> bool {
+        let source = definition.source.as_ref();
+        if !self.include.is_empty() && !source.map(|it| self.include.contains(it)).unwrap_or(false) {
+            return false;
+        }
+        if source.map(|it| self.exclude.contains(it)).unwrap_or(false) {
+            return false;
+        }
+        if self.tags.is_empty() {
+            return true;
+        }
+        definition.content.iter().any(|it| {
+            if let Text::Tag(tag) = it {
+                self.tags.contains(tag)
+            } else {
+                false
+            }
+        })
+    }
 }
 
 impl Text {
@@ -571,3 +1204,13 @@ impl Text {
         }
     }
 }
+
+
+#[cfg(test)]#[test]
+fn test_supports_trigram() {
+    assert!(supports_trigram("3.34.0"));
+    assert!(supports_trigram("3.40.1"));
+    assert!(!supports_trigram("3.31.1"));
+    assert!(!supports_trigram("3.8.11"));
+    assert!(!supports_trigram(""));
+}
@@ -39,6 +39,8 @@ pub enum AppError {
     Format(std::fmt::Error),
     #[fail(display = "IO error: {}", 0)]
     Io(std::io::Error),
+    #[fail(display = "JSON error: {}", 0)]
+    Json(json::Error),
     #[fail(display = "Database error: {}", 0)]
     Kv(kv::Error),
     #[fail(display = "Failed to lock")]
@@ -49,18 +51,24 @@ pub enum AppError {
     NotFound,
     #[fail(display = "Parser error: {}", 0)]
     Pom(pom::Error),
+    #[fail(display = "Connection pool error: {}", 0)]
+    Pool(diesel::r2d2::PoolError),
     #[fail(display = "Readline error: {}", 0)]
     Readline(rustyline::error::ReadlineError),
     #[fail(display = "Regular expression error: {}", 0)]
     Regex(regex::Error),
     #[fail(display = "Error: {}", 0)]
     Standard(String),
+    #[fail(display = "Config error: {}", 0)]
+    Toml(toml::de::Error),
     #[fail(display = "Unexpect error: {}", 0)]
     Unexpect(&'static str),
     #[fail(display = "UTF8 conversion error: {}", 0)]
     Utf8(std::str::Utf8Error),
     #[fail(display = "Void")]
     Void,
+    #[fail(display = "Zip error: {}", 0)]
+    Zip(zip::result::ZipError),
 }
 
 
@@ -81,7 +89,9 @@ define_error!(csv::Error, Csv);
 define_error!(serde_json::Error, Deserialization);
 define_error!(diesel::result::ConnectionError, DieselConnection);
 define_error!(diesel::result::Error, Diesel);
+define_error!(json::Error, Json);
 define_error!(kv::Error, Kv);
+define_error!(diesel::r2d2::PoolError, Pool);
 define_error!(pom::Error, Pom);
 define_error!(regex::Error, Regex);
 define_error!(rustyline::error::ReadlineError, Readline);
@@ -89,6 +99,8 @@ define_error!(std::fmt::Error, Format);
 define_error!(std::num::ParseFloatError, FloatingNumberFormat);
 define_error!(std::num::ParseIntError, NumberFormat);
 define_error!(std::str::Utf8Error, Utf8);
+define_error!(toml::de::Error, Toml);
+define_error!(zip::result::ZipError, Zip);
 
 
 
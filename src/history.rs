@@ -0,0 +1,85 @@
+
+use if_let_return::if_let_some;
+use serde_derive::Serialize;
+
+use crate::db::model;
+use crate::dictionary::Dictionary;
+use crate::errors::{AppResult, AppResultU};
+use crate::str_utils::fix_word;
+
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Cli,
+    Gui,
+    Http,
+    Shell,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Item {
+    pub term: String,
+    pub source: String,
+    pub found: bool,
+    pub created_at: String,
+}
+
+
+impl Dictionary {
+    pub fn record_history(&self, word: &str, source: Source, found: bool) -> AppResultU {
+        if_let_some!(term = fix_word(word.trim()), Ok(()));
+
+        let connection = self.connect_db()?;
+
+        diesel_query!(history [E R] {
+            diesel::insert_into(d::history)
+                .values((d::term.eq(&term), d::source.eq(source.as_str()), d::found.eq(found)))
+                .execute(&connection)?;
+        });
+
+        Ok(())
+    }
+
+    /// Newest first
+    pub fn history(&self, limit: Option<usize>) -> AppResult<Vec<Item>> {
+        let connection = self.connect_db()?;
+
+        let found = diesel_query!(history, History [Q E R] {
+            let q = d::history.order(d::id.desc()).into_boxed();
+            let q = if let Some(limit) = limit {
+                q.limit(limit as i64)
+            } else {
+                q
+            };
+            q.load::<History>(&connection)?
+        });
+
+        Ok(found.into_iter().map(Item::from).collect())
+    }
+}
+
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        use self::Source::*;
+
+        match self {
+            Cli => "cli",
+            Gui => "gui",
+            Http => "http",
+            Shell => "shell",
+        }
+    }
+}
+
+impl From<model::History> for Item {
+    fn from(it: model::History) -> Self {
+        Item {
+            term: it.term,
+            source: it.source,
+            found: it.found,
+            created_at: it.created_at,
+        }
+    }
+}
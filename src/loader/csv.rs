@@ -1,35 +1,113 @@
 
-use std::io::Read;
+use std::io::BufRead;
+
+use csv::{ReaderBuilder, StringRecord};
 
-use crate::dictionary::DictionaryWriter;
 use crate::errors::{AppError, AppResultU};
-use crate::loader::Loader;
+use crate::loader::{Loader, Records, Units};
 use crate::parser::eijiro::parse_line;
-use crate::types::DictionaryFormat::Csv;
+use crate::types::DictionaryFormat::{self, Csv, Tsv};
+
 
 
+/// Written by `dump`
+pub const CSV_HEADER: &[&str] = &["term", "definition", "aliases", "inflections", "level"];
 
-#[derive(Default)]
-pub struct CsvLoader();
 
+/// Also for TSV
+pub struct CsvLoader {
+    format: DictionaryFormat,
+}
+
+
+impl CsvLoader {
+    pub fn tsv() -> Self {
+        CsvLoader { format: Tsv }
+    }
+}
+
+impl Default for CsvLoader {
+    fn default() -> Self {
+        CsvLoader { format: Csv }
+    }
+}
 
 impl Loader for CsvLoader {
-    fn load<S: Read>(&self, source: &mut S, writer: &mut DictionaryWriter) -> AppResultU {
-        println!("Reading CSV...");
-
-        let mut source = csv::Reader::from_reader(source);
-        for columns in source.records() {
-            let columns = columns?;
-            if 2 < columns.len() {
-                return Err(AppError::DictionaryFormat(Csv, "Too many columns"))
-            }
-            if columns.len() < 2 {
-                return Err(AppError::DictionaryFormat(Csv, "Too few columns"))
+    type Unit = StringRecord;
+
+    fn units<'a, S: BufRead + 'a>(&self, source: &'a mut S) -> Units<'a, StringRecord> {
+        // Hand-written sources usually have no header, so skip only the one written by `dump`
+        let mut builder = ReaderBuilder::new();
+        builder.has_headers(false);
+        if self.format == Tsv {
+            builder.delimiter(b'\t').quoting(false);
+        }
+
+        let records = builder.from_reader(source).into_records().enumerate().filter(|(index, it)| {
+            match it {
+                Ok(record) => !(*index == 0 && record.iter().eq(CSV_HEADER.iter().cloned())),
+                Err(_) => true,
             }
+        });
+
+        Box::new(records.map(|(_, it)| {
+            let position = match &it {
+                Ok(record) => record.position(),
+                Err(error) => error.position(),
+            };
+            let line = position.map(|it| it.line() as usize).unwrap_or(0);
+            (line, it.map_err(AppError::from))
+        }))
+    }
 
-            writer.define(&columns[0], parse_line(&columns[1])?)?;
+    fn parse(&self, columns: StringRecord, records: &mut Records) -> AppResultU {
+        // term, definition[, aliases, inflections, level]
+        if 5 < columns.len() {
+            return Err(AppError::DictionaryFormat(self.format, "Too many columns"))
+        }
+        if columns.len() < 2 {
+            return Err(AppError::DictionaryFormat(self.format, "Too few columns"))
+        }
+
+        let key = &columns[0];
+        records.define(key, parse_line(&columns[1])?);
+
+        if let Some(aliases) = columns.get(2) {
+            for alias in split_words(aliases) {
+                records.alias(alias, key, false);
+            }
+        }
+        if let Some(inflections) = columns.get(3) {
+            for inflection in split_words(inflections) {
+                records.alias(inflection, key, true);
+            }
+        }
+        if let Some(level) = columns.get(4) {
+            let level = level.trim();
+            if !level.is_empty() {
+                records.levelize(level.parse()?, key);
+            }
         }
 
         Ok(())
     }
 }
+
+
+fn split_words(s: &str) -> impl Iterator<Item = &str> {
+    s.split(',').map(str::trim).filter(|it| !it.is_empty())
+}
+
+
+#[cfg(test)]
+fn count_units(loader: &CsvLoader, source: &str) -> usize {
+    let mut source = source.as_bytes();
+    loader.units(&mut source).filter(|(_, it)| it.is_ok()).count()
+}
+
+#[cfg(test)]#[test]
+fn test_units() {
+    assert_eq!(count_units(&CsvLoader::tsv(), "cat\t猫\ndog\t犬\n"), 2);
+    assert_eq!(count_units(&CsvLoader::default(), "cat,猫\ndog,犬\n"), 2);
+    assert_eq!(count_units(&CsvLoader::default(), "term,definition,aliases,inflections,level\ncat,猫,,,1\n"), 1);
+}
@@ -1,14 +1,10 @@
 
-use std::io::Read;
+use std::io::BufRead;
 
-use encoding::DecoderTrap::Replace;
-use encoding::Encoding;
-use encoding::all::WINDOWS_31J;
 use if_let_return::if_let_some;
 
-use crate::dictionary::DictionaryWriter;
 use crate::errors::{AppError, AppResultU};
-use crate::loader::Loader;
+use crate::loader::{numbered, Loader, Records, Units};
 use crate::parser::eijiro::parse_line;
 use crate::str_utils::{scan_words, WordType};
 
@@ -19,66 +15,59 @@ pub struct EijiroLoader();
 
 
 impl Loader for EijiroLoader {
-    fn load<S: Read>(&self, source: &mut S, writer: &mut DictionaryWriter) -> AppResultU {
-        println!("Reading Eijiro...");
-        let mut buffer = vec![];
-        let _ = source.read_to_end(&mut buffer)?;
+    type Unit = String;
 
-        println!("Encoding...");
-        let source = WINDOWS_31J.decode(&buffer, Replace).map_err(|_| AppError::Encoding("MS932"))?;
+    fn units<'a, S: BufRead + 'a>(&self, source: &'a mut S) -> Units<'a, String> {
+        numbered(source.lines().map(|it| it.map_err(AppError::from)))
+    }
 
-        for line in source.lines() {
-            if line.starts_with("■") {
-                load_line(writer, &line[3..])?;
-            }
+    fn parse(&self, line: String, records: &mut Records) -> AppResultU {
+        if line.starts_with("■") {
+            load_line(records, &line[3..])?;
         }
-
-
         Ok(())
     }
 }
 
 
-fn load_line(writer: &mut DictionaryWriter, line: &str) -> AppResultU {
-    fn extract_aliases(writer: &mut DictionaryWriter, key: &str, right: &str) -> AppResultU {
-        fn extract(writer: &mut DictionaryWriter, key: &str, right: &str, word_type: WordType, pattern: &str, for_lemmatization: bool) -> AppResultU {
+fn load_line(records: &mut Records, line: &str) -> AppResultU {
+    fn extract_aliases(records: &mut Records, key: &str, right: &str) {
+        fn extract(records: &mut Records, key: &str, right: &str, word_type: WordType, pattern: &str, for_lemmatization: bool) {
             if let Some(found) = right.find(pattern) {
                 let right = &right[found + pattern.len()..];
                 let right = read_until_symbols(&right);
                 if !right.is_empty() {
                     for it in scan_words(word_type, right) {
-                        writer.alias(&it, key, for_lemmatization)?;
+                        records.alias(&it, key, for_lemmatization);
                     }
                 }
             }
-            Ok(())
         }
 
         let right = right.replace('（', "").replace('）', "");
-        extract(writer, key, &right, WordType::English, "【変化】", true)?;
+        extract(records, key, &right, WordType::English, "【変化】", true);
         // cat-o'-nine-tails
-        extract(writer, key, &right, WordType::English, "【同】", false)?;
-        extract(writer, key, &right, WordType::Katakana, "【＠】", false)?;
-        extract(writer, key, &right, WordType::English, "【略】", false)
+        extract(records, key, &right, WordType::English, "【同】", false);
+        extract(records, key, &right, WordType::Katakana, "【＠】", false);
+        extract(records, key, &right, WordType::English, "【略】", false);
     }
 
-    fn extract_link(writer: &mut DictionaryWriter, key: &str, mut right: &str) -> AppResultU {
+    fn extract_link(records: &mut Records, key: &str, mut right: &str) {
         if let Some(l) = right.find("＝<→") {
             right = &right[l + 7..];
         } else if let Some(l) = right.find("<→") {
             right = &right[l + 4..];
         } else {
-            return Ok(())
+            return;
         }
         if let Some(r) = right.find('>') {
-            writer.alias(&right[0..r], key, false)?;
+            records.alias(&right[0..r], key, false);
             // cat-o'-nine-tails
-            writer.alias(key, &right[0..r], false)?;
+            records.alias(key, &right[0..r], false);
         }
-        Ok(())
     }
 
-    fn extract_level(writer: &mut DictionaryWriter, key: &str, mut right: &str) -> AppResultU {
+    fn extract_level(records: &mut Records, key: &str, mut right: &str) -> AppResultU {
         if let Some(l) = right.find("【レベル】") {
             right = &right[l + 15..];
             let mut n = "".to_owned();
@@ -90,21 +79,22 @@ fn load_line(writer: &mut DictionaryWriter, line: &str) -> AppResultU {
                 }
             }
             let level: u8 = n.parse()?;
-            writer.levelize(level, key)?;
+            records.levelize(level, key);
         }
         Ok(())
     }
 
-    fn parse(writer: &mut DictionaryWriter, key: &str, source: &str) -> AppResultU {
+    fn parse(records: &mut Records, key: &str, source: &str) -> AppResultU {
         let parsed = parse_line(&source)?;
 
         for it in &parsed {
             if let crate::dictionary::Text::Tag(t) = it {
-                writer.tag(key, t)?;
+                records.tag(key, t);
             }
         }
 
-        writer.define(key, parsed)
+        records.define(key, parsed);
+        Ok(())
     }
 
     if_let_some!(sep = line.find(" : "), Ok(()));
@@ -116,9 +106,9 @@ fn load_line(writer: &mut DictionaryWriter, line: &str) -> AppResultU {
         let left = left.trim();
         let tag = extract_tag_name(&tag[1..(r - l)]);
 
-        extract_link(writer, left, &right)?;
-        extract_aliases(writer, left, &right)?;
-        extract_level(writer, left, &right)?;
+        extract_link(records, left, &right);
+        extract_aliases(records, left, &right);
+        extract_level(records, left, &right)?;
 
         let right = if let Some(tag) = tag {
             format!("{{{}}} {}", tag, right)
@@ -126,14 +116,14 @@ fn load_line(writer: &mut DictionaryWriter, line: &str) -> AppResultU {
             right.to_string()
         };
 
-        return parse(writer, left, &right);
+        return parse(records, left, &right);
     }
 
-    extract_link(writer, left, &right)?;
-    extract_aliases(writer, left, right)?;
-    extract_level(writer, left, &right)?;
+    extract_link(records, left, &right);
+    extract_aliases(records, left, right);
+    extract_level(records, left, &right)?;
 
-    parse(writer, left, &right)
+    parse(records, left, &right)
 }
 
 
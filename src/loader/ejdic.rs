@@ -1,11 +1,10 @@
 
-use std::io::Read;
+use std::io::BufRead;
 
 use if_let_return::if_let_some;
 
-use crate::dictionary::DictionaryWriter;
-use crate::errors::AppResultU;
-use crate::loader::Loader;
+use crate::errors::{AppError, AppResultU};
+use crate::loader::{numbered, Loader, Records, Units};
 use crate::parser::ejdic::parse_line;
 
 
@@ -15,21 +14,19 @@ pub struct EjdicLoader();
 
 
 impl Loader for EjdicLoader {
-    fn load<S: Read>(&self, source: &mut S, writer: &mut DictionaryWriter) -> AppResultU {
-        println!("Reading EJDIC...");
-        let mut buffer = "".to_owned();
-        let _ = source.read_to_string(&mut buffer)?;
+    type Unit = String;
 
-        for line in buffer.lines() {
-            load_line(writer, line)?;
-        }
+    fn units<'a, S: BufRead + 'a>(&self, source: &'a mut S) -> Units<'a, String> {
+        numbered(source.lines().map(|it| it.map_err(AppError::from)))
+    }
 
-        Ok(())
+    fn parse(&self, line: String, records: &mut Records) -> AppResultU {
+        load_line(records, &line)
     }
 }
 
 
-fn load_line(writer: &mut DictionaryWriter, line: &str) -> AppResultU {
+fn load_line(records: &mut Records, line: &str) -> AppResultU {
     if_let_some!(tab = line.find('\t'), Ok(()));
     let keys = &line[0..tab];
     let definitions = &line[tab+1..];
@@ -37,10 +34,10 @@ fn load_line(writer: &mut DictionaryWriter, line: &str) -> AppResultU {
     let mut keys = keys.split(',');
     let key = keys.next().unwrap();
     for definition in definitions.split(" / ") {
-        writer.define(key, parse_line(definition)?)?;
+        records.define(key, parse_line(definition)?);
     }
     for alias in keys {
-        writer.alias(&alias.trim(), key, false)?;
+        records.alias(&alias.trim(), key, false);
     }
 
     Ok(())
@@ -0,0 +1,217 @@
+
+use std::io::{BufRead, Read, Result as IOResult};
+
+use encoding::all::{UTF_16BE, UTF_16LE, WINDOWS_31J};
+use encoding::DecoderTrap::Replace;
+use encoding::EncodingRef;
+
+
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const UTF16BE_BOM: &[u8] = b"\xfe\xff";
+const UTF16LE_BOM: &[u8] = b"\xff\xfe";
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// CP932
+    ShiftJis,
+    Utf16Be,
+    Utf16Le,
+    Utf8,
+}
+
+/// Source transcoded to UTF-8
+pub struct Utf8Reader<R: BufRead> {
+    buffer: Vec<u8>,
+    consumed: usize,
+    encoding: Encoding,
+    inner: R,
+}
+
+
+impl Encoding {
+    /// Guess from the head of the source
+    pub fn detect(head: &[u8]) -> Self {
+        if head.starts_with(UTF8_BOM) {
+            return Encoding::Utf8;
+        }
+        if head.starts_with(UTF16BE_BOM) {
+            return Encoding::Utf16Be;
+        }
+        if head.starts_with(UTF16LE_BOM) {
+            return Encoding::Utf16Le;
+        }
+
+        match std::str::from_utf8(head) {
+            Ok(_) => Encoding::Utf8,
+            // The head may end in the middle of a character
+            Err(error) if error.error_len().is_none() => Encoding::Utf8,
+            Err(_) => Encoding::ShiftJis,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        use self::Encoding::*;
+
+        match self {
+            ShiftJis => "shift_jis",
+            Utf16Be => "utf-16be",
+            Utf16Le => "utf-16le",
+            Utf8 => "utf-8",
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> String {
+        match self.encoding_ref() {
+            Some(encoding) => encoding.decode(bytes, Replace).unwrap_or_else(|_| "".to_owned()),
+            None => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        use self::Encoding::*;
+
+        match self {
+            ShiftJis => &[],
+            Utf16Be => UTF16BE_BOM,
+            Utf16Le => UTF16LE_BOM,
+            Utf8 => UTF8_BOM,
+        }
+    }
+
+    fn encoding_ref(self) -> Option<EncodingRef> {
+        use self::Encoding::*;
+
+        match self {
+            ShiftJis => Some(WINDOWS_31J),
+            Utf16Be => Some(UTF_16BE),
+            Utf16Le => Some(UTF_16LE),
+            Utf8 => None,
+        }
+    }
+}
+
+impl<R: BufRead> Utf8Reader<R> {
+    pub fn new(mut inner: R, encoding: Encoding) -> IOResult<Self> {
+        let bom = encoding.bom();
+        if !bom.is_empty() && inner.fill_buf()?.starts_with(bom) {
+            inner.consume(bom.len());
+        }
+        Ok(Utf8Reader { buffer: vec![], consumed: 0, encoding, inner })
+    }
+
+    /// Decode the next line (Shift_JIS never uses b'\n' in multibyte characters)
+    fn decode_next(&mut self) -> IOResult<()> {
+        let mut raw = vec![];
+        match self.encoding {
+            Encoding::ShiftJis => {
+                self.inner.read_until(b'\n', &mut raw)?;
+            },
+            _ => self.read_utf16_line(&mut raw)?,
+        };
+        self.buffer = self.encoding.decode(&raw).into_bytes();
+        self.consumed = 0;
+        Ok(())
+    }
+
+    /// Read until the code unit of b'\n', skipping the bytes of b'\n' in the other code units (e.g. "上" is 0x4E0A)
+    fn read_utf16_line(&mut self, raw: &mut Vec<u8>) -> IOResult<()> {
+        loop {
+            if self.inner.read_until(b'\n', raw)? == 0 || raw.last() != Some(&b'\n') {
+                return Ok(());
+            }
+            let aligned = raw.len() % 2 == 0;
+            match self.encoding {
+                Encoding::Utf16Be if aligned && raw[raw.len() - 2] == 0 => return Ok(()),
+                Encoding::Utf16Le if !aligned => {
+                    let next = match self.inner.fill_buf()?.first() {
+                        Some(next) => *next,
+                        None => return Ok(()),
+                    };
+                    self.inner.consume(1);
+                    raw.push(next);
+                    if next == 0 {
+                        return Ok(());
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for Utf8Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IOResult<usize> {
+        let size = {
+            let available = self.fill_buf()?;
+            let size = available.len().min(buf.len());
+            buf[0..size].copy_from_slice(&available[0..size]);
+            size
+        };
+        self.consume(size);
+        Ok(size)
+    }
+}
+
+impl<R: BufRead> BufRead for Utf8Reader<R> {
+    fn fill_buf(&mut self) -> IOResult<&[u8]> {
+        if self.encoding == Encoding::Utf8 {
+            return self.inner.fill_buf();
+        }
+        if self.buffer.len() <= self.consumed {
+            self.decode_next()?;
+        }
+        Ok(&self.buffer[self.consumed..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.encoding == Encoding::Utf8 {
+            self.inner.consume(amt);
+        } else {
+            self.consumed += amt;
+        }
+    }
+}
+
+
+#[cfg(test)]#[test]
+fn test_detect() {
+    assert_eq!(Encoding::detect(b"\xef\xbb\xbfcat"), Encoding::Utf8);
+    assert_eq!(Encoding::detect(b"\xff\xfec\x00"), Encoding::Utf16Le);
+    assert_eq!(Encoding::detect("■猫".as_bytes()), Encoding::Utf8);
+    // "■猫" truncated
+    assert_eq!(Encoding::detect(&"■猫".as_bytes()[0..5]), Encoding::Utf8);
+    // "■猫" in Shift_JIS
+    assert_eq!(Encoding::detect(b"\x81\xa1\x94\x4c"), Encoding::ShiftJis);
+}
+
+#[cfg(test)]#[test]
+fn test_utf8_reader() {
+    // "■猫\r\n犬\nbird"
+    let source: &[u8] = b"\x81\xa1\x94\x4c\r\n\x8c\xa2\nbird";
+    let lines: Vec<String> = Utf8Reader::new(source, Encoding::ShiftJis).unwrap().lines().map(Result::unwrap).collect();
+    assert_eq!(lines, vec!["■猫", "犬", "bird"]);
+
+    let source: &[u8] = b"\xff\xfec\x00a\x00t\x00\n\x00";
+    let lines: Vec<String> = Utf8Reader::new(source, Encoding::Utf16Le).unwrap().lines().map(Result::unwrap).collect();
+    assert_eq!(lines, vec!["cat"]);
+
+    // "上\n\u{a0a}\nx"
+    let source: &[u8] = b"\x0a\x4e\n\x00\x0a\x0a\n\x00x\x00";
+    let mut reader = Utf8Reader::new(source, Encoding::Utf16Le).unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "上\n");
+    let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+    assert_eq!(lines, vec!["\u{a0a}", "x"]);
+
+    // "上\n\u{a0a}\nx"
+    let source: &[u8] = b"\xfe\xff\x4e\x0a\x00\n\x0a\x0a\x00\n\x00x";
+    let lines: Vec<String> = Utf8Reader::new(source, Encoding::Utf16Be).unwrap().lines().map(Result::unwrap).collect();
+    assert_eq!(lines, vec!["上", "\u{a0a}", "x"]);
+
+    let source: &[u8] = b"\xef\xbb\xbfcat\ndog";
+    let lines: Vec<String> = Utf8Reader::new(source, Encoding::Utf8).unwrap().lines().map(Result::unwrap).collect();
+    assert_eq!(lines, vec!["cat", "dog"]);
+}
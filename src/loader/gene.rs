@@ -1,13 +1,9 @@
 
-use std::io::Read;
+use std::io::BufRead;
+use std::iter::from_fn;
 
-use encoding::DecoderTrap::Replace;
-use encoding::Encoding;
-use encoding::all::WINDOWS_31J;
-
-use crate::dictionary::DictionaryWriter;
 use crate::errors::{AppError, AppResultU};
-use crate::loader::Loader;
+use crate::loader::{Loader, Records, Units};
 use crate::parser::gene::parse_line;
 
 
@@ -17,25 +13,22 @@ pub struct GeneLoader();
 
 
 impl Loader for GeneLoader {
-    fn load<S: Read>(&self, source: &mut S, writer: &mut DictionaryWriter) -> AppResultU {
-        println!("Reading GENE...");
-        let mut buffer = vec![];
-        let _ = source.read_to_end(&mut buffer)?;
-
-        println!("Encoding...");
-        let source = WINDOWS_31J.decode(&buffer, Replace).map_err(|_| AppError::Encoding("MS932"))?;
-
-        let mut key_buffer = None;
-
-        for line in source.lines().skip(2) {
-            if let Some(key) = key_buffer.take() {
-                let definition = parse_line(line)?;
-                writer.define(key, definition)?;
-            } else {
-                key_buffer = Some(line)
-            }
-        }
+    /// Key line and definition line
+    type Unit = (String, String);
+
+    fn units<'a, S: BufRead + 'a>(&self, source: &'a mut S) -> Units<'a, Self::Unit> {
+        let mut lines = source.lines().map(|it| it.map_err(AppError::from)).skip(2);
+        let mut line = 1;
+        Box::new(from_fn(move || {
+            let key = lines.next()?;
+            let definition = lines.next()?;
+            line += 2;
+            Some((line, key.and_then(|key| definition.map(|definition| (key, definition)))))
+        }))
+    }
 
+    fn parse(&self, (key, definition): Self::Unit, records: &mut Records) -> AppResultU {
+        records.define(&key, parse_line(&definition)?);
         Ok(())
     }
 }
@@ -1,52 +1,97 @@
 
-use std::io::Read;
+use std::io::BufRead;
+use std::iter::once;
 
 use json::{parse, JsonValue};
 use regex::Regex;
 
-use crate::dictionary::{DictionaryWriter, Text};
-use crate::errors::{AppError, AppResultU};
-use crate::loader::Loader;
+use crate::dictionary::Text;
+use crate::errors::{AppError, AppResult, AppResultU};
+use crate::loader::{Loader, Records, Units};
+use crate::parser::eijiro::parse_line;
 use crate::types::DictionaryFormat::JsonSimpleKeyValue;
 
 
 
-#[derive(Default)]
-pub struct JsonSimpleKeyValueLoader();
+pub struct JsonSimpleKeyValueLoader {
+    number: Regex,
+}
 
 
-impl Loader for JsonSimpleKeyValueLoader {
-    fn load<S: Read>(&self, source: &mut S, writer: &mut DictionaryWriter) -> AppResultU {
-        println!("Reading JSON as simple key value object...");
+impl Default for JsonSimpleKeyValueLoader {
+    fn default() -> Self {
+        JsonSimpleKeyValueLoader { number: Regex::new(r"\d+\. ").unwrap() }
+    }
+}
 
-        let number = Regex::new(r"\d+\. ")?;
+impl Loader for JsonSimpleKeyValueLoader {
+    /// Term and value
+    type Unit = (String, JsonValue);
 
+    fn units<'a, S: BufRead + 'a>(&self, source: &'a mut S) -> Units<'a, Self::Unit> {
         let mut buffer: String = "".to_owned();
-        source.read_to_string(&mut buffer)?;
-        if let Ok(JsonValue::Object(obj)) = parse(&buffer) {
-            for (term, def) in obj.iter() {
-                let def = match def {
-                    JsonValue::String(def) => def,
-                    JsonValue::Short(def) => def.as_str(),
-                    _ => return Err(AppError::DictionaryFormat(JsonSimpleKeyValue, "Invalid type")),
-                };
-
-                let mut left = 0;
-                for m in number.find_iter(def) {
-                    let start = m.start();
-                    if 0 < (start - left) {
-                        writer.define(&term, text(&def[left .. start]))?;
-                    }
-                    left = start;
-                }
-                writer.define(&term, text(&def[left ..]))?;
+        if let Err(error) = source.read_to_string(&mut buffer) {
+            return Box::new(once((0, Err(error.into()))));
+        }
+
+        let mut obj = match parse(&buffer) {
+            Ok(JsonValue::Object(obj)) => obj,
+            Ok(_) => return Box::new(once((0, Err(AppError::DictionaryFormat(JsonSimpleKeyValue, "Not an object"))))),
+            Err(error) => return Box::new(once((0, Err(error.into())))),
+        };
+        drop(buffer);
+
+        let result: Vec<_> = obj.iter_mut().enumerate().map(|(index, (term, def))| {
+            (index + 1, Ok((term.to_owned(), def.take())))
+        }).collect();
+        Box::new(result.into_iter())
+    }
+
+    fn parse(&self, (term, def): Self::Unit, records: &mut Records) -> AppResultU {
+        let def = match &def {
+            JsonValue::String(def) => def,
+            JsonValue::Short(def) => def.as_str(),
+            JsonValue::Object(_) => return load_object(records, &term, &def),
+            _ => return Err(AppError::DictionaryFormat(JsonSimpleKeyValue, "Invalid type")),
+        };
+
+        let mut left = 0;
+        for m in self.number.find_iter(def) {
+            let start = m.start();
+            if 0 < (start - left) {
+                records.define(&term, text(&def[left .. start]));
             }
+            left = start;
         }
+        records.define(&term, text(&def[left ..]));
+
         Ok(())
     }
 }
 
 
+/// {"definitions": [EIJIRO_FORMAT_TEXT], "aliases": [WORD], "inflections": [WORD], "level": N}
+fn load_object(records: &mut Records, term: &str, value: &JsonValue) -> AppResultU {
+    fn strings(value: &JsonValue) -> AppResult<Vec<&str>> {
+        value.members().map(|it| it.as_str().ok_or(AppError::DictionaryFormat(JsonSimpleKeyValue, "Invalid type"))).collect()
+    }
+
+    for definition in strings(&value["definitions"])? {
+        records.define(term, parse_line(definition)?);
+    }
+    for alias in strings(&value["aliases"])? {
+        records.alias(alias, term, false);
+    }
+    for inflection in strings(&value["inflections"])? {
+        records.alias(inflection, term, true);
+    }
+    if let Some(level) = value["level"].as_u8() {
+        records.levelize(level, term);
+    }
+
+    Ok(())
+}
+
 fn text(s: &str) -> Vec<Text> {
     vec![Text::Definition(s.to_owned())]
 }
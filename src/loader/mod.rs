@@ -1,17 +1,133 @@
 
-use std::io::Read;
+use std::io::BufRead;
 
-use crate::dictionary::{DictionaryWriter};
-use crate::errors::AppResultU;
+use rayon::prelude::*;
+
+use crate::dictionary::{DictionaryWriter, Text};
+use crate::errors::{AppResult, AppResultU};
+use crate::loader::report::Report;
 
 pub mod csv;
 pub mod eijiro;
 pub mod ejdic;
+pub mod encoding;
 pub mod gene;
 pub mod json_simple_key_value;
+pub mod progress;
+pub mod report;
+
+
+
+/// Units parsed at once by the worker threads
+const CHUNK_SIZE: usize = 10_000;
+
+
+pub trait Loader: Sync {
+    /// Piece of the source parsed independently (e.g. a line)
+    type Unit: Send;
+
+    /// Units with the line numbers (The entry numbers for JSON)
+    fn units<'a, S: BufRead + 'a>(&self, source: &'a mut S) -> Units<'a, Self::Unit>;
+    fn parse(&self, unit: Self::Unit, records: &mut Records) -> AppResultU;
+}
+
+pub type Units<'a, T> = Box<dyn Iterator<Item = (usize, AppResult<T>)> + 'a>;
+
+/// Operations for `DictionaryWriter` made by the worker threads
+#[derive(Default)]
+pub struct Records {
+    records: Vec<Record>,
+}
+
+enum Record {
+    Alias(String, String, bool),
+    Define(String, Vec<Text>),
+    Levelize(u8, String),
+    Tag(String, String),
+}
+
+
+
+/// Parse the units in parallel, and pass the records to `f` in the source order
+pub fn load<L: Loader, S: BufRead, F>(loader: &L, source: &mut S, report: &mut Report, mut f: F) -> AppResultU where F: FnMut(Records) -> AppResultU {
+    let mut units = loader.units(source);
+
+    loop {
+        let chunk: Vec<(usize, AppResult<L::Unit>)> = units.by_ref().take(CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        let parsed: Vec<(usize, AppResult<Records>)> = chunk.into_par_iter().map(|(line, unit)| {
+            let parsed = unit.and_then(|unit| {
+                let mut records = Records::default();
+                loader.parse(unit, &mut records)?;
+                Ok(records)
+            });
+            (line, parsed)
+        }).collect();
+
+        let mut records = Records::default();
+        for (line, it) in parsed {
+            match it {
+                Ok(it) => {
+                    report.unparsed(line, it.errors());
+                    records.records.extend(it.records);
+                },
+                Err(error) => report.skip(line, error)?,
+            }
+        }
+        f(records)?;
+    }
+}
+
+/// Number the lines from 1
+pub fn numbered<'a, T: 'a, I: Iterator<Item = AppResult<T>> + 'a>(iter: I) -> Units<'a, T> {
+    Box::new(iter.enumerate().map(|(index, it)| (index + 1, it)))
+}
+
+
+impl Records {
+    pub fn alias(&mut self, from: &str, to: &str, for_lemmatization: bool) {
+        self.records.push(Record::Alias(from.to_owned(), to.to_owned(), for_lemmatization));
+    }
+
+    pub fn define(&mut self, key: &str, content: Vec<Text>) {
+        self.records.push(Record::Define(key.to_owned(), content));
+    }
+
+    pub fn levelize(&mut self, level: u8, key: &str) {
+        self.records.push(Record::Levelize(level, key.to_owned()));
+    }
 
+    pub fn tag(&mut self, term: &str, tag: &str) {
+        self.records.push(Record::Tag(term.to_owned(), tag.to_owned()));
+    }
 
+    /// Unparsed fragments in the definitions
+    fn errors(&self) -> Vec<String> {
+        let mut result = vec![];
+        for record in &self.records {
+            if let Record::Define(_, content) = record {
+                for text in content {
+                    if let Text::Error(fragment) = text {
+                        result.push(fragment.to_owned());
+                    }
+                }
+            }
+        }
+        result
+    }
 
-pub trait Loader {
-    fn load<S: Read>(&self, source: &mut S, writer: &mut DictionaryWriter) -> AppResultU;
+    pub fn write(self, writer: &mut DictionaryWriter) -> AppResultU {
+        for record in self.records {
+            match record {
+                Record::Alias(from, to, for_lemmatization) => writer.alias(&from, &to, for_lemmatization)?,
+                Record::Define(key, content) => writer.define(&key, content)?,
+                Record::Levelize(level, key) => writer.levelize(level, &key)?,
+                Record::Tag(term, tag) => writer.tag(&term, &tag)?,
+            }
+        }
+        Ok(())
+    }
 }
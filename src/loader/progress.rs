@@ -0,0 +1,97 @@
+
+use std::io::{BufRead, Read, Result as IOResult, stdout, Write};
+use std::time::{Duration, Instant};
+
+
+
+const INTERVAL: Duration = Duration::from_millis(500);
+
+
+/// Show lines/sec and ETA while the source is read
+pub struct ProgressReader<R: BufRead> {
+    bytes: u64,
+    inner: R,
+    lines: u64,
+    shown_at: Instant,
+    started_at: Instant,
+    total: u64,
+}
+
+
+impl<R: BufRead> ProgressReader<R> {
+    /// `total`: Size of the source in bytes
+    pub fn new(inner: R, total: u64) -> Self {
+        let now = Instant::now();
+        ProgressReader { bytes: 0, inner, lines: 0, shown_at: now, started_at: now, total }
+    }
+
+    pub fn finish(&mut self) {
+        self.show();
+        println!();
+    }
+
+    fn count(&mut self, bytes: usize, lines: usize) {
+        self.bytes += bytes as u64;
+        self.lines += lines as u64;
+
+        if INTERVAL <= self.shown_at.elapsed() {
+            self.show();
+            self.shown_at = Instant::now();
+        }
+    }
+
+    fn show(&self) {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        let lines_per_sec = if 0.0 < elapsed { self.lines as f64 / elapsed } else { 0.0 };
+        print!("\r{} lines, {:.0} lines/sec", self.lines, lines_per_sec);
+        if 0 < self.total {
+            let ratio = self.bytes as f64 / self.total as f64;
+            print!(", {:.1}%", ratio * 100.0);
+            if 0.0 < ratio {
+                print!(", ETA {}", format_seconds(elapsed / ratio - elapsed));
+            }
+        }
+        print!("   ");
+        let _ = stdout().flush();
+    }
+}
+
+impl<R: BufRead> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IOResult<usize> {
+        let size = self.inner.read(buf)?;
+        self.count(size, count_lines(&buf[0..size]));
+        Ok(size)
+    }
+}
+
+impl<R: BufRead> BufRead for ProgressReader<R> {
+    fn fill_buf(&mut self) -> IOResult<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The buffer is already filled, so this never reads the source
+        let lines = self.inner.fill_buf().map(|it| count_lines(&it[0..amt.min(it.len())])).unwrap_or(0);
+        self.count(amt, lines);
+        self.inner.consume(amt);
+    }
+}
+
+
+fn count_lines(buf: &[u8]) -> usize {
+    buf.iter().filter(|it| **it == b'\n').count()
+}
+
+fn format_seconds(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+
+#[cfg(test)]#[test]
+fn test_format_seconds() {
+    assert_eq!(format_seconds(0.0), "0:00:00");
+    assert_eq!(format_seconds(61.5), "0:01:01");
+    assert_eq!(format_seconds(3725.0), "1:02:05");
+    assert_eq!(format_seconds(-1.0), "0:00:00");
+}
@@ -0,0 +1,100 @@
+
+use std::fmt;
+use std::io::Write;
+
+use crate::errors::{AppError, AppResultU};
+
+
+
+/// Problems found while loading the sources
+#[derive(Default)]
+pub struct Report {
+    /// Number of the entries that have unparsed fragments
+    error_entries: usize,
+    file: String,
+    lenient: bool,
+    skipped: Vec<Problem>,
+    unparsed: Vec<Problem>,
+}
+
+struct Problem {
+    file: String,
+    line: usize,
+    reason: String,
+}
+
+
+impl Report {
+    /// `lenient`: Skip the bad records instead of aborting
+    pub fn new(lenient: bool) -> Self {
+        Report { lenient, ..Default::default() }
+    }
+
+    pub fn set_file(&mut self, file: &str) {
+        self.file = file.to_owned();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty() && self.unparsed.is_empty()
+    }
+
+    /// Returns the error back unless it is skippable
+    pub fn skip(&mut self, line: usize, error: AppError) -> AppResultU {
+        if !self.lenient || is_fatal(&error) {
+            return Err(error);
+        }
+        let problem = self.problem(line, error.to_string());
+        eprintln!("\rSkipped: {}", problem);
+        self.skipped.push(problem);
+        Ok(())
+    }
+
+    pub fn unparsed(&mut self, line: usize, fragments: Vec<String>) {
+        if fragments.is_empty() {
+            return;
+        }
+        self.error_entries += 1;
+        for fragment in fragments {
+            let problem = self.problem(line, fragment);
+            self.unparsed.push(problem);
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Skipped: {} records, Unparsed: {} fragments in {} entries",
+            self.skipped.len(),
+            self.unparsed.len(),
+            self.error_entries)
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> AppResultU {
+        writeln!(out, "# {}", self.summary())?;
+        for it in &self.skipped {
+            writeln!(out, "skipped\t{}", it)?;
+        }
+        for it in &self.unparsed {
+            writeln!(out, "unparsed\t{}", it)?;
+        }
+        Ok(())
+    }
+
+    fn problem(&self, line: usize, reason: String) -> Problem {
+        Problem { file: self.file.clone(), line, reason }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.reason)
+    }
+}
+
+
+fn is_fatal(error: &AppError) -> bool {
+    match error {
+        AppError::Csv(error) => error.is_io_error(),
+        AppError::Io(_) => true,
+        _ => false,
+    }
+}
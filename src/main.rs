@@ -11,17 +11,24 @@ use structopt::clap::AppSettings;
 
 #[macro_use] mod db;
 mod command;
+mod config;
 mod correction;
 mod delay;
 mod dictionary;
 mod errors;
+mod history;
 mod loader;
+mod notebook;
 mod pager;
 mod parser;
 mod path;
+mod query;
+mod review;
 mod screen;
 mod str_utils;
+mod theme;
 mod types;
+mod user_dictionary;
 
 use crate::errors::{AppError, AppResultU};
 
@@ -32,14 +39,16 @@ use crate::errors::{AppError, AppResultU};
 pub struct Opt {
     #[structopt(subcommand)]
     pub command: Option<Command>,
-    /// Dictionary name
-    #[structopt(short, long)]
-    pub dictionary: Option<String>
+    /// Dictionary names (Comma separated, in priority order)
+    #[structopt(short, long, use_delimiter = true)]
+    pub dictionary: Vec<String>,
 }
 
 #[derive(StructOpt, Debug)]
 #[structopt(setting = AppSettings::InferSubcommands)]
 pub enum Command {
+    /// Add a definition to the user dictionary
+    Add(command::user::AddOpt),
     /// Analyze text (STDIN) using SVL
     Analyze(command::analyze::Opt),
     /// Build dictionary
@@ -49,8 +58,16 @@ pub enum Command {
     /// Access dictionary database using sqlite
     #[structopt(alias = "db")]
     Database(command::database::Opt),
+    /// Spaced repetition drill
+    Drill(command::drill::Opt),
+    /// Dump the dictionary in a format loadable by `build`
+    Dump(command::dump::Opt),
+    /// Edit the definitions in the user dictionary with $EDITOR
+    Edit(command::user::EditOpt),
     /// Export the definitions for the given words (STDIN)
     Export(command::export::Opt),
+    /// Lookup history
+    History(command::history::Opt),
     /// Output HTML fragment
     Html(command::html::Opt),
     /// Output keys
@@ -64,12 +81,23 @@ pub enum Command {
     Like(command::lookup::LikeOpt),
     /// Lookup
     Lookup(command::lookup::LookupOpt),
+    /// Starred words and memos
+    Notebook(command::notebook::Opt),
     /// Display the file paths using by eitaro
     Path,
+    /// Remove the word from the user dictionary
+    Remove(command::user::RemoveOpt),
+    /// Reverse lookup (Japanese to English)
+    #[structopt(alias = "rl")]
+    Rlookup(command::lookup::ReverseLookupOpt),
     /// HTTP Server
     Server(command::http::Opt),
     /// Interactive shell
     Shell(command::lookup::ShellOpt),
+    /// List the words having the tag
+    Tag(command::tags::TagOpt),
+    /// List the tags with the number of words
+    Tags,
     /// Untypo
     Untypo(command::untypo::Opt),
     /// Play wordle
@@ -86,49 +114,71 @@ fn _main() -> AppResultU {
 
     let opt = Opt::from_args();
 
-    let dict = opt.dictionary.as_ref().map(AsRef::as_ref);
-    let dictionary_path: PathBuf = path::get_dictionary_path(dict).expect("Failed to get dictionary path");
+    let dictionary_names = if opt.dictionary.is_empty() { &config::get().dictionaries } else { &opt.dictionary };
+    let dictionary_paths: Vec<PathBuf> = path::get_dictionary_paths(dictionary_names).expect("Failed to get dictionary path");
+    // The primary dictionary for the commands to write
+    let dictionary_path = &dictionary_paths[0];
 
     if let Some(command) = opt.command {
 
         match command {
+            Add(opt) =>
+                command::user::add(opt),
             Analyze(opt) =>
-                command::analyze::analyze(opt, &dictionary_path),
+                command::analyze::analyze(opt, &dictionary_paths),
             Build(opt) =>
-                command::builder::build_dictionary(opt, &dictionary_path),
+                command::builder::build_dictionary(opt, dictionary_path),
             Completions(opt) =>
                 command::completions::generate(opt, Opt::clap()),
             Database(opt) =>
-                command::database::shell(opt, &dictionary_path),
+                command::database::shell(opt, dictionary_path),
+            Drill(opt) =>
+                command::drill::drill(opt, &dictionary_paths),
+            Dump(opt) =>
+                command::dump::dump(opt, &dictionary_paths),
+            Edit(opt) =>
+                command::user::edit(opt),
             Export(opt) =>
-                command::export::export(opt, &dictionary_path),
+                command::export::export(opt, &dictionary_paths),
+            History(opt) =>
+                command::history::history(opt, &dictionary_paths),
             Html(opt) =>
-                command::html::lookup(opt, &dictionary_path),
+                command::html::lookup(opt, &dictionary_paths),
             Lemmas(opt) =>
-                command::lemmas::lemmas(opt, &dictionary_path),
+                command::lemmas::lemmas(opt, &dictionary_paths),
             Shell(opt) =>
-                command::lookup::shell(opt, &dictionary_path),
+                command::lookup::shell(opt, &dictionary_paths),
             Lemmatize(opt) =>
-                command::lemmatize::lemmatize(opt, &dictionary_path),
+                command::lemmatize::lemmatize(opt, &dictionary_paths),
             Level(opt) =>
-                command::level::level(opt, &dictionary_path),
+                command::level::level(opt, &dictionary_paths),
             Like(opt) =>
-                command::lookup::like(opt, &dictionary_path),
+                command::lookup::like(opt, &dictionary_paths),
             Lookup(opt) =>
-                command::lookup::lookup(opt, &dictionary_path),
+                command::lookup::lookup(opt, &dictionary_paths),
+            Notebook(opt) =>
+                command::notebook::notebook(opt, &dictionary_paths),
             Path =>
-                command::path::path(&dictionary_path),
+                command::path::path(&dictionary_paths),
+            Remove(opt) =>
+                command::user::remove(opt),
+            Rlookup(opt) =>
+                command::lookup::reverse_lookup(opt, &dictionary_paths),
             Server(opt) =>
-                command::http::start_server(opt, dictionary_path),
+                command::http::start_server(opt, dictionary_paths),
+            Tag(opt) =>
+                command::tags::tag(opt, &dictionary_paths),
+            Tags =>
+                command::tags::tags(&dictionary_paths),
             Untypo(opt) =>
-                command::untypo::untypo(opt, &dictionary_path),
+                command::untypo::untypo(opt, &dictionary_paths),
             Wordle(opt) =>
-                command::wordle::play(opt, &dictionary_path),
+                command::wordle::play(opt, &dictionary_paths),
             Words(opt) =>
-                command::words::extract(opt, &dictionary_path),
+                command::words::extract(opt, &dictionary_paths),
         }
     } else if let Some(Command::Shell(opt)) = Opt::from_iter(&["", "shell"]).command {
-        command::lookup::shell(opt, &dictionary_path)
+        command::lookup::shell(opt, &dictionary_paths)
     } else {
         panic!("WTF: {:?}", Opt::from_iter(&["shell"]))
     }
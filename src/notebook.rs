@@ -0,0 +1,79 @@
+
+use if_let_return::if_let_some;
+use serde_derive::Serialize;
+
+use crate::db::model;
+use crate::dictionary::Dictionary;
+use crate::errors::{AppError, AppResult, AppResultU};
+use crate::str_utils::fix_word;
+
+
+
+#[derive(Debug, Serialize)]
+pub struct Item {
+    pub term: String,
+    pub memo: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+
+impl Dictionary {
+    /// Star the word. The memo is updated only if it is given.
+    pub fn star(&self, word: &str, memo: Option<&str>) -> AppResultU {
+        let term = fix_word(word.trim()).ok_or(AppError::Eitaro("Empty word"))?;
+        let connection = self.connect_db()?;
+
+        diesel_query!([R] {
+            use diesel::sql_types::{Nullable, Text};
+
+            diesel::sql_query(
+                "INSERT INTO notebook (term, memo) VALUES (?, ?) \
+                 ON CONFLICT(term) DO UPDATE SET \
+                   memo = COALESCE(excluded.memo, notebook.memo), \
+                   updated_at = CURRENT_TIMESTAMP")
+                .bind::<Text, _>(&term)
+                .bind::<Nullable<Text>, _>(memo)
+                .execute(&connection)?;
+        });
+
+        Ok(())
+    }
+
+    /// Returns false if the word is not starred
+    pub fn unstar(&self, word: &str) -> AppResult<bool> {
+        if_let_some!(term = fix_word(word.trim()), Ok(false));
+        let connection = self.connect_db()?;
+
+        let deleted = diesel_query!(notebook [E Q R] {
+            diesel::delete(d::notebook.filter(d::term.eq(&term)))
+                .execute(&connection)?
+        });
+
+        Ok(0 < deleted)
+    }
+
+    pub fn notebook(&self) -> AppResult<Vec<Item>> {
+        let connection = self.connect_db()?;
+
+        let found = diesel_query!(notebook, Notebook [Q R] {
+            d::notebook
+                .order(d::term)
+                .load::<Notebook>(&connection)?
+        });
+
+        Ok(found.into_iter().map(Item::from).collect())
+    }
+}
+
+
+impl From<model::Notebook> for Item {
+    fn from(it: model::Notebook) -> Self {
+        Item {
+            term: it.term,
+            memo: it.memo,
+            created_at: it.created_at,
+            updated_at: it.updated_at,
+        }
+    }
+}
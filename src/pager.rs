@@ -1,14 +1,16 @@
 
 use std::process::{Command, Stdio, ChildStdin};
 
+use crate::config;
 use crate::errors::{AppError, AppResultU};
 
 
 
 pub fn with_pager<F>(f: F) -> AppResultU
 where F: FnOnce(&mut ChildStdin) -> AppResultU {
-    let mut c = Command::new("less");
-    c.args(&["--quit-if-one-screen", "--RAW-CONTROL-CHARS", "--no-init"]);
+    let pager = &config::get().pager;
+    let mut c = Command::new(&pager.command);
+    c.args(&pager.args);
     c.stdin(Stdio::piped());
     c.stdout(Stdio::inherit());
 
@@ -12,7 +12,43 @@ const SPECIALS: &str = "{}〈〉《》◆■〔〕\n";
 
 pub fn parse_line(input: &str) -> Result<Vec<Text>, pom::Error> {
     let mut input = TextInput::new(input);
-    text().parse(&mut input)
+    let mut result = text().parse(&mut input)?;
+    push_rest(&input, &mut result);
+    Ok(result)
+}
+
+/// Inverse of `parse_line`
+pub fn unparse(content: &[Text]) -> String {
+    use self::Text::*;
+
+    let mut result = "".to_owned();
+    let mut open_ended = false;
+
+    for text in content {
+        match text {
+            Definition(s) | Error(s) => {
+                if open_ended {
+                    result.push(' ');
+                }
+                result.push_str(s);
+            }
+            Annot(s) => result.push_str(&format!("〈{}〉", s)),
+            Class(s) => result.push_str(&format!("《{}》", s)),
+            Countability(c) => result.push_str(&format!("〔{}〕", c)),
+            Etymology(s) => result.push_str(&format!("【語源】{}", s)),
+            Example(s) => result.push_str(&format!("■・{}", s)),
+            Information(s) => result.push_str(&format!("◆{}", s)),
+            Note(s) => result.push_str(&format!("〔{}〕", s)),
+            Tag(s) => result.push_str(&format!("{{{}}}", s)),
+            Word(s) => result.push_str(&format!("#{}\n", s)),
+        }
+        open_ended = match text {
+            Definition(_) | Error(_) | Etymology(_) | Example(_) | Information(_) => true,
+            _ => false,
+        };
+    }
+
+    result
 }
 
 fn with_spaces(p: Parser<char, Text>) -> Parser<char, Text> {
@@ -105,4 +141,23 @@ fn test_parser() {
         parse_line("■meow :"),
         Ok(vec![
            Text::Definition("■meow :".to_string())]));
+
+    assert_eq!(
+        parse_line("ブラブラ〈米俗"),
+        Ok(vec![
+           Text::Definition("ブラブラ".to_string()),
+           Text::Error("〈米俗".to_string())]));
+}
+
+#[cfg(test)]#[test]
+fn test_unparse() {
+    let content = vec![
+        Text::Tag("自動".to_string()),
+        Text::Class("名".to_string()),
+        Text::Annot("米俗".to_string()),
+        Text::Definition("ブラブラする".to_string()),
+        Text::Example("I dangle.".to_string()),
+        Text::Note("古".to_string()),
+        Text::Information("meow".to_string())];
+    assert_eq!(parse_line(&unparse(&content)), Ok(content));
 }
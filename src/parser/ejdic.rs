@@ -12,7 +12,44 @@ const SPECIALS: &str = "〈〉《》〔〕\n";
 
 pub fn parse_line(input: &str) -> Result<Vec<Text>, pom::Error> {
     let mut input = TextInput::new(input);
-    text().parse(&mut input)
+    let mut result = text().parse(&mut input)?;
+    push_rest(&input, &mut result);
+    Ok(result)
+}
+
+/// Inverse of `parse_line` (Only the notations supported by EJDIC are preserved)
+pub fn unparse(content: &[Text]) -> String {
+    use self::Text::*;
+
+    let mut result = "".to_owned();
+    let mut plain = false;
+
+    for text in content {
+        let current_plain = match text {
+            Annot(s) | Class(s) => {
+                result.push_str(&format!("《{}》", s));
+                false
+            },
+            Countability(c) => {
+                result.push_str(&format!("〈{}〉", c));
+                false
+            },
+            Note(s) | Tag(s) => {
+                result.push_str(&format!("〔{}〕", s));
+                false
+            },
+            Definition(s) | Error(s) | Etymology(s) | Example(s) | Information(s) | Word(s) => {
+                if plain {
+                    result.push(' ');
+                }
+                result.push_str(s);
+                true
+            },
+        };
+        plain = current_plain;
+    }
+
+    result.replace('\t', " ").replace(" / ", " ")
 }
 
 fn with_spaces(p: Parser<char, Text>) -> Parser<char, Text> {
@@ -1,6 +1,21 @@
 
+use pom::TextInput;
+
+use crate::dictionary::Text;
+
+
+
+
 
 pub fn v2s(s: Vec<char>) -> String {
     let s: String = s.into_iter().collect();
     s.trim().to_owned()
 }
+
+/// Keep the rest that could not be parsed as `Text::Error`
+pub fn push_rest(input: &TextInput, result: &mut Vec<Text>) {
+    let rest = input.text[input.position..].trim();
+    if !rest.is_empty() {
+        result.push(Text::Error(rest.to_owned()));
+    }
+}
@@ -8,6 +8,12 @@ use app_dirs::{app_dir, AppDataType, AppDirsError, AppInfo};
 const APP_INFO: AppInfo = AppInfo { name: "eitaro", author: "anekos" };
 
 
+pub fn get_config_path() -> Result<PathBuf, AppDirsError> {
+    let mut path = app_dir(AppDataType::UserConfig, &APP_INFO, "")?;
+    path.push("config.toml");
+    Ok(path)
+}
+
 pub fn get_dictionary_path(name: Option<&str>) -> Result<PathBuf, AppDirsError> {
     let mut result = app_dir(AppDataType::UserCache, &APP_INFO, "dictionary")?;
     if let Some(name) = name {
@@ -18,8 +24,22 @@ pub fn get_dictionary_path(name: Option<&str>) -> Result<PathBuf, AppDirsError>
     Ok(result)
 }
 
+/// The default dictionary if no name is given
+pub fn get_dictionary_paths(names: &[String]) -> Result<Vec<PathBuf>, AppDirsError> {
+    if names.is_empty() {
+        return Ok(vec![get_dictionary_path(None)?]);
+    }
+    names.iter().map(|it| get_dictionary_path(Some(it.as_str()))).collect()
+}
+
 pub fn get_history_path() -> Result<PathBuf, AppDirsError> {
     let mut path = app_dir(AppDataType::UserCache, &APP_INFO, "history")?;
     path.push("history.txt");
     Ok(path)
 }
+
+pub fn get_user_dictionary_path() -> Result<PathBuf, AppDirsError> {
+    let mut path = app_dir(AppDataType::UserData, &APP_INFO, "user")?;
+    path.push("dictionary.json");
+    Ok(path)
+}
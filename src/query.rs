@@ -0,0 +1,174 @@
+
+// Query for full text search
+//
+//   cat dog      Both of "cat" and "dog"
+//   "black cat"  Phrase
+//   -dog         Without "dog" (`NOT dog` is also OK)
+//
+// Terms match any part of the text (The trigram index has no word boundary)
+
+
+
+// The trigram tokenizer can not find any term shorter than this
+const MIN_FTS_TERM_LENGTH: usize = 3;
+
+
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Term {
+    pub text: String,
+    pub negative: bool,
+}
+
+
+impl Query {
+    pub fn parse(s: &str) -> Self {
+        let mut terms = vec![];
+        let mut negative = false;
+        let mut chars = s.chars().peekable();
+
+        loop {
+            while chars.peek().map_or(false, |it| it.is_whitespace()) {
+                chars.next();
+            }
+            if chars.peek().is_none() {
+                break;
+            }
+
+            if chars.peek() == Some(&'-') {
+                chars.next();
+                negative = true;
+            }
+
+            let mut text = "".to_owned();
+            let quoted = chars.peek() == Some(&'"');
+            if quoted {
+                chars.next();
+                for c in &mut chars {
+                    if c == '"' {
+                        break;
+                    }
+                    text.push(c);
+                }
+            } else {
+                while let Some(c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    text.push(*c);
+                    chars.next();
+                }
+            }
+
+            if !quoted && text == "NOT" {
+                negative = true;
+                continue;
+            }
+
+            // `cat*` is accepted for compatibility, but the same as `cat`
+            if quoted {
+                if chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+            } else if text.ends_with('*') {
+                text.pop();
+            }
+
+            let text = text.trim().to_owned();
+            if !text.is_empty() {
+                terms.push(Term { text, negative });
+            }
+            negative = false;
+        }
+
+        Query { terms }
+    }
+
+    /// Only for the terms long enough (The others are checked by `matches_short_terms`).
+    /// Returns `None` if the query can not be evaluated by FTS5
+    pub fn to_fts(&self) -> Option<String> {
+        let mut positives = vec![];
+        let mut negatives = vec![];
+
+        for term in self.terms.iter().filter(|it| !it.is_short()) {
+            let s = format!("\"{}\"", term.text.replace('"', "\"\""));
+            if term.negative {
+                negatives.push(s);
+            } else {
+                positives.push(s);
+            }
+        }
+
+        if positives.is_empty() {
+            return None;
+        }
+
+        let mut result = positives.join(" AND ");
+        for negative in negatives {
+            result.push_str(" NOT ");
+            result.push_str(&negative);
+        }
+
+        Some(result)
+    }
+
+    /// Case insensitive for ASCII, as `LIKE`
+    pub fn matches_short_terms(&self, text: &str) -> bool {
+        let text = text.to_ascii_lowercase();
+        self.terms.iter().filter(|it| it.is_short()).all(|it| {
+            text.contains(&it.text.to_ascii_lowercase()) != it.negative
+        })
+    }
+}
+
+impl Term {
+    fn is_short(&self) -> bool {
+        self.text.chars().count() < MIN_FTS_TERM_LENGTH
+    }
+}
+
+
+
+#[cfg(test)]#[test]
+fn test_parse() {
+    fn term(text: &str, negative: bool) -> Term {
+        Term { text: text.to_owned(), negative }
+    }
+
+    assert_eq!(
+        Query::parse(" cat  dog "),
+        Query { terms: vec![term("cat", false), term("dog", false)] });
+    assert_eq!(
+        Query::parse(r#""black cat" kit* -dog NOT "hot dog""#),
+        Query { terms: vec![
+            term("black cat", false),
+            term("kit", false),
+            term("dog", true),
+            term("hot dog", true)] });
+    assert_eq!(
+        Query::parse("猫"),
+        Query { terms: vec![term("猫", false)] });
+}
+
+#[cfg(test)]#[test]
+fn test_to_fts() {
+    assert_eq!(
+        Query::parse(r#"cat "black cat" kit* -dog"#).to_fts(),
+        Some(r#""cat" AND "black cat" AND "kit" NOT "dog""#.to_owned()));
+    assert_eq!(Query::parse("猫").to_fts(), None);
+    assert_eq!(Query::parse("-dog").to_fts(), None);
+    assert_eq!(Query::parse("cat 猫 -ox").to_fts(), Some(r#""cat""#.to_owned()));
+}
+
+#[cfg(test)]#[test]
+fn test_matches_short_terms() {
+    let query = Query::parse("cat 猫 -ox");
+    assert!(query.matches_short_terms("Cat: 猫"));
+    assert!(!query.matches_short_terms("cat: ネコ"));
+    assert!(!query.matches_short_terms("猫 and OX"));
+    assert!(Query::parse("cat").matches_short_terms("dog"));
+}
@@ -0,0 +1,175 @@
+
+// SM-2 spaced repetition
+// ref: https://www.supermemo.com/en/archives1990-2015/english/ol/sm2
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::model;
+use crate::dictionary::Dictionary;
+use crate::errors::{AppResult, AppResultU};
+
+
+
+const INITIAL_EASINESS: f64 = 2.5;
+const MIN_EASINESS: f64 = 1.3;
+pub const MAX_GRADE: u8 = 5;
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    pub easiness: f64,
+    /// Days
+    pub interval: i32,
+    pub repetitions: i32,
+    /// Days since UNIX epoch
+    pub due: i32,
+}
+
+
+impl Schedule {
+    pub fn new(today: i32) -> Self {
+        Schedule {
+            easiness: INITIAL_EASINESS,
+            interval: 0,
+            repetitions: 0,
+            due: today,
+        }
+    }
+
+    /// `grade`: 0 (complete blackout) to 5 (perfect response)
+    pub fn review(&self, grade: u8, today: i32) -> Self {
+        let grade = grade.min(MAX_GRADE);
+        let q = f64::from(MAX_GRADE - grade);
+
+        // Forgotten items start the repetitions again, without changing the E-Factor
+        if grade < 3 {
+            return Schedule {
+                easiness: self.easiness,
+                interval: 1,
+                repetitions: 0,
+                due: today + 1,
+            };
+        }
+
+        let interval = match self.repetitions {
+            0 => 1,
+            1 => 6,
+            _ => (f64::from(self.interval) * self.easiness).round() as i32,
+        };
+        let repetitions = self.repetitions + 1;
+        let easiness = (self.easiness + (0.1 - q * (0.08 + q * 0.02))).max(MIN_EASINESS);
+
+        Schedule {
+            easiness,
+            interval,
+            repetitions,
+            due: today + interval,
+        }
+    }
+}
+
+
+impl Dictionary {
+    pub fn due_reviews(&self, today: i32) -> AppResult<Vec<String>> {
+        let connection = self.connect_db()?;
+
+        let found = diesel_query!(reviews [E Q R] {
+            d::reviews
+                .filter(d::due.le(today))
+                .order((d::due, d::term))
+                .select(d::term)
+                .load::<String>(&connection)?
+        });
+
+        Ok(found)
+    }
+
+    pub fn reviewed_terms(&self) -> AppResult<Vec<String>> {
+        let connection = self.connect_db()?;
+
+        let found = diesel_query!(reviews [Q R] {
+            d::reviews
+                .select(d::term)
+                .load::<String>(&connection)?
+        });
+
+        Ok(found)
+    }
+
+    pub fn get_schedule(&self, term: &str) -> AppResult<Option<Schedule>> {
+        let connection = self.connect_db()?;
+
+        let found = diesel_query!(reviews, Review [E O Q R] {
+            d::reviews
+                .filter(d::term.eq(term))
+                .first::<Review>(&connection)
+                .optional()?
+        });
+
+        Ok(found.map(Schedule::from))
+    }
+
+    pub fn save_schedule(&self, term: &str, schedule: &Schedule) -> AppResultU {
+        let connection = self.connect_db()?;
+
+        diesel_query!(reviews [E R] {
+            diesel::replace_into(d::reviews)
+                .values((
+                    d::term.eq(term),
+                    d::easiness.eq(schedule.easiness),
+                    d::interval_days.eq(schedule.interval),
+                    d::repetitions.eq(schedule.repetitions),
+                    d::due.eq(schedule.due)))
+                .execute(&connection)?;
+        });
+
+        Ok(())
+    }
+}
+
+
+impl From<model::Review> for Schedule {
+    fn from(it: model::Review) -> Self {
+        Schedule {
+            easiness: it.easiness,
+            interval: it.interval_days,
+            repetitions: it.repetitions,
+            due: it.due,
+        }
+    }
+}
+
+
+pub fn today() -> i32 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    (now.as_secs() / (24 * 60 * 60)) as i32
+}
+
+
+
+#[cfg(test)]#[test]
+fn test_review() {
+    let s = Schedule::new(100);
+
+    let s = s.review(5, 100);
+    assert_eq!((s.interval, s.repetitions, s.due), (1, 1, 101));
+    assert!((s.easiness - 2.6).abs() < 1e-9);
+
+    let s = s.review(4, 101);
+    assert_eq!((s.interval, s.repetitions, s.due), (6, 2, 107));
+    assert!((s.easiness - 2.6).abs() < 1e-9);
+
+    let s = s.review(3, 107);
+    assert_eq!((s.interval, s.repetitions, s.due), (16, 3, 123));
+    assert!((s.easiness - 2.46).abs() < 1e-9);
+
+    let s = s.review(1, 123);
+    assert_eq!((s.interval, s.repetitions, s.due), (1, 0, 124));
+    assert!((s.easiness - 2.46).abs() < 1e-9);
+
+    let s = s.review(4, 124);
+    assert_eq!((s.interval, s.repetitions, s.due), (1, 1, 125));
+
+    let s = (0..10).fold(s, |s, _| s.review(3, 125));
+    assert!((s.easiness - MIN_EASINESS).abs() < 1e-9);
+}
@@ -1,12 +1,11 @@
 
-use std::io::{Error as IOError, Write};
+use std::io::{Error as IOError, stdout, Write};
 use std::sync::mpsc::Receiver;
 
-use deco::{dprintln, dwrite, dwriteln};
-
-use crate::dictionary::{Entry, Text};
+use crate::dictionary::{dictionary_header, Entry};
 use crate::errors::AppResultU;
 use crate::pager::with_pager;
+use crate::theme::{self, Style};
 
 
 
@@ -22,37 +21,28 @@ pub fn main(rx: Receiver<Option<Vec<Entry>>>) -> AppResultU {
 }
 
 pub fn print(entries: Vec<Entry>) -> AppResultU {
-    fn color_key<W: Write>(out: &mut W, key: &str) -> Result<(), IOError> {
-        dwriteln!(out, [black on_yellow bold "{}" !] key)
-    }
-
-    fn color<W: Write>(out: &mut W, text: &Text) -> Result<(), IOError> {
-        use self::Text::*;
-
-        match text {
-            Annot(s) => dwrite!(out, [yellow "{}" !] s),
-            Class(s) => dwrite!(out, [blue "{}" !] s),
-            Countability(c) => dwrite!(out, [yellow bold "{}" !] c),
-            Definition(s) => dwrite!(out, [white bold "{}" !] s),
-            Error(s) => dwrite!(out, [red bold "{}" !] s),
-            Etymology(s) => dwrite!(out, [magenta bold "語源" ! " {}"] s),
-            Example(s) => dwrite!(out, [green "{}" !] s),
-            Information(s) => dwrite!(out, [cyan "{}" !] s),
-            Note(s) => write!(out, "{}", s),
-            Tag(s) => dwrite!(out, [red bold "{}" !] s),
-            Word(s) => color_key(out, &s),
-        }
-    }
+    let theme = theme::current();
 
     with_pager(|out| {
-        for entry in entries {
-            color_key(out, &entry.key)?;
+        for (index, entry) in entries.iter().enumerate() {
+            if let Some(name) = dictionary_header(&entries, index) {
+                write_styled(out, &theme.dictionary, &format!(" {} ", name))?;
+                writeln!(out)?;
+            }
+            write_styled(out, &theme.key, &entry.key)?;
+            writeln!(out)?;
             for definition in &entry.definitions {
+                if let Some(source) = &definition.source {
+                    write_styled(out, &theme.source, source)?;
+                    write!(out, " ")?;
+                }
                 for (index, text) in definition.content.iter().enumerate() {
                     if 0 < index {
                         write!(out, " ")?;
                     }
-                    color(out, text)?;
+                    for (style, s) in theme.parts(text) {
+                        write_styled(out, style, &s)?;
+                    }
                 }
                 writeln!(out)?;
             }
@@ -62,5 +52,26 @@ pub fn print(entries: Vec<Entry>) -> AppResultU {
 }
 
 pub fn print_not_found() {
-    dprintln!([black on_red "{}" !] "Not Found");
+    let mut out = stdout();
+    let _ = write_styled(&mut out, &theme::current().not_found, "Not Found").and_then(|_| writeln!(out));
+}
+
+fn write_styled<W: Write>(out: &mut W, style: &Style, s: &str) -> Result<(), IOError> {
+    let mut codes = vec![];
+    if style.bold {
+        codes.push(1);
+    }
+    if let Some(fg) = style.fg {
+        codes.push(fg.ansi_fg());
+    }
+    if let Some(bg) = style.bg {
+        codes.push(bg.ansi_bg());
+    }
+
+    if codes.is_empty() {
+        return write!(out, "{}", s);
+    }
+
+    let codes: Vec<String> = codes.iter().map(u8::to_string).collect();
+    write!(out, "\x1b[{}m{}\x1b[0m", codes.join(";"), s)
 }
@@ -3,11 +3,12 @@ use std::process::exit;
 use std::sync::mpsc::Receiver;
 use std::time::Duration;
 
-use easycurses::{colorpair, ColorPair, CursorVisibility, EasyCurses, Input, TimeoutMode};
+use easycurses::{colorpair, Color, ColorPair, CursorVisibility, EasyCurses, Input, TimeoutMode};
 use if_let_return::if_let_some;
 use structopt::StructOpt;
 
-use crate::dictionary::{Entry, Text};
+use crate::dictionary::{dictionary_header, Entry};
+use crate::theme::{self, Color as ThemeColor, Style};
 
 
 
@@ -26,45 +27,23 @@ pub struct Opt {
 pub fn main(rx: &Receiver<Option<Vec<Entry>>>, opt: Opt, bind_to: &str) {
     use easycurses::Color::*;
 
-    fn color_key(out: &mut EasyCurses, key: &str) {
-        out.set_color_pair(colorpair!(Black on Yellow));
-        out.set_bold(true);
-        out.win.addstr(key);
-        out.win.addstr("\n");
-        out.set_bold(false);
-    }
-
-    fn color(out: &mut EasyCurses, text: &Text) {
-        use self::Text::*;
+    let theme = theme::current();
+    let default_fg = to_color(theme.foreground.unwrap_or(ThemeColor::White));
+    let default_bg = to_color(theme.background.unwrap_or(ThemeColor::Black));
 
-        fn write(out: &mut EasyCurses, text: &str, color_pair: ColorPair, bold: bool) {
-            out.set_color_pair(color_pair);
-            if bold {
-                out.set_bold(true);
-            }
-            out.win.addstr(text);
-            if bold {
-                out.set_bold(false);
-            }
+    let write = |out: &mut EasyCurses, style: &Style, text: &str| {
+        let fg = style.fg.map(to_color).unwrap_or(default_fg);
+        let bg = style.bg.map(to_color).unwrap_or(default_bg);
+        out.set_color_pair(ColorPair::new(fg, bg));
+        if style.bold {
+            out.set_bold(true);
         }
-
-        match text {
-            Annot(s) => write(out, s, colorpair!(Yellow on Black), false),
-            Countability(c) => write(out, &c.to_string(), colorpair!(Yellow on Black), false),
-            Class(s) => write(out, s, colorpair!(Blue on Black), false),
-            Definition(s) => write(out, s, colorpair!(White on Black), true),
-            Error(s) => write(out, s, colorpair!(Red on Black), true),
-            Etymology(s) => {
-                write(out, "語源 ", colorpair!(Magenta on Black), true);
-                write(out, s, colorpair!(White on Black), false);
-            },
-            Example(s) => write(out, s, colorpair!(Green on Black), false),
-            Information(s) => write(out, s, colorpair!(Cyan on Black), false),
-            Note(s) => write(out, s, colorpair!(White on Black), false),
-            Tag(s) => write(out, s, colorpair!(Red on Black), false),
-            Word(s) => write(out, s, colorpair!(Black on Yellow), false),
+        out.win.addstr(text);
+        if style.bold {
+            out.set_bold(false);
         }
-    }
+        out.set_color_pair(ColorPair::new(default_fg, default_bg));
+    };
 
     // DO NOT REMOVE THIS BLOCK (EasyCurses should finalize)
     {
@@ -79,7 +58,7 @@ pub fn main(rx: &Receiver<Option<Vec<Entry>>>, opt: Opt, bind_to: &str) {
         out.clear();
         out.set_color_pair(colorpair!(Black on White));
         out.win.addstr(concat!(env!("CARGO_PKG_NAME"), " v", env!("CARGO_PKG_VERSION")));
-        out.set_color_pair(colorpair!(White on Black));
+        out.set_color_pair(ColorPair::new(default_fg, default_bg));
         out.win.addstr(format!("\non {}", bind_to));
         out.win.addstr("\n\npress q to quit");
         out.refresh();
@@ -97,23 +76,31 @@ pub fn main(rx: &Receiver<Option<Vec<Entry>>>, opt: Opt, bind_to: &str) {
             while let Ok(entries) = rx.recv_timeout(timeout) {
                 out.clear();
                 if let Some(entries) = entries {
-                    for entry in entries {
-                        color_key(&mut out, &entry.key);
+                    for (index, entry) in entries.iter().enumerate() {
+                        if let Some(name) = dictionary_header(&entries, index) {
+                            write(&mut out, &theme.dictionary, &format!(" {} ", name));
+                            out.win.addstr("\n");
+                        }
+                        write(&mut out, &theme.key, &entry.key);
+                        out.win.addstr("\n");
                         for definition in &entry.definitions {
+                            if let Some(source) = &definition.source {
+                                write(&mut out, &theme.source, source);
+                                out.win.addstr(" ");
+                            }
                             for (index, text) in definition.content.iter().enumerate() {
                                 if 0 < index {
                                     out.win.addstr(" ");
                                 }
-                                color(&mut out, text);
+                                for (style, s) in theme.parts(text) {
+                                    write(&mut out, style, &s);
+                                }
                             }
                             out.win.addstr("\n");
                         }
                     }
                 } else {
-                    out.set_color_pair(colorpair!(White on Red));
-                    out.set_bold(true);
-                    out.win.addstr("Not Found");
-                    out.set_bold(false);
+                    write(&mut out, &theme.not_found, "Not Found");
                 }
                 rc = out.get_cursor_rc();
                 out.refresh();
@@ -157,7 +144,7 @@ pub fn main(rx: &Receiver<Option<Vec<Entry>>>, opt: Opt, bind_to: &str) {
                         continue;
                     }
 
-                    out.set_color_pair(colorpair!(White on Black));
+                    out.set_color_pair(ColorPair::new(default_fg, default_bg));
                     out.move_rc(rows - 1, face_col);
                     out.delete_line();
                     out.win.addstr(FACES[face_index]);
@@ -185,3 +172,17 @@ pub fn main(rx: &Receiver<Option<Vec<Entry>>>, opt: Opt, bind_to: &str) {
 
     exit(0);
 }
+
+
+fn to_color(color: ThemeColor) -> Color {
+    match color {
+        ThemeColor::Black => Color::Black,
+        ThemeColor::Red => Color::Red,
+        ThemeColor::Green => Color::Green,
+        ThemeColor::Yellow => Color::Yellow,
+        ThemeColor::Blue => Color::Blue,
+        ThemeColor::Magenta => Color::Magenta,
+        ThemeColor::Cyan => Color::Cyan,
+        ThemeColor::White => Color::White,
+    }
+}
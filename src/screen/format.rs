@@ -0,0 +1,199 @@
+
+use std::io::Write;
+use std::str::FromStr;
+
+use serde_derive::Serialize;
+
+use crate::dictionary::{dictionary_header, Entry};
+use crate::errors::{AppError, AppResultU};
+use crate::screen::plain::{write_content, write_definition};
+
+
+
+pub const FORMAT_NAMES: &[&str] = &["json", "jsonl", "markdown", "tsv"];
+
+
+/// Machine readable output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// An array of entries
+    Json,
+    /// An entry per line
+    Jsonl,
+    Markdown,
+    /// key, source, definition
+    Tsv,
+}
+
+/// Writes a record per query (for batch lookup)
+pub struct RecordWriter<W: Write> {
+    count: usize,
+    format: Option<Format>,
+    out: W,
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    query: &'a str,
+    found: bool,
+    entries: &'a [Entry],
+}
+
+
+impl FromStr for Format {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use self::Format::*;
+
+        match s {
+            "json" => Ok(Json),
+            "jsonl" => Ok(Jsonl),
+            "markdown" | "md" => Ok(Markdown),
+            "tsv" => Ok(Tsv),
+            _ => Err(AppError::Eitaro("Unknown output format")),
+        }
+    }
+}
+
+
+impl<W: Write> RecordWriter<W> {
+    pub fn new(out: W, format: Option<Format>) -> Self {
+        RecordWriter { count: 0, format, out }
+    }
+
+    pub fn write(&mut self, query: &str, entries: Option<&[Entry]>) -> AppResultU {
+        use self::Format::*;
+
+        let out = &mut self.out;
+        let record = Record { query, found: entries.is_some(), entries: entries.unwrap_or(&[]) };
+
+        match self.format {
+            None => {
+                writeln!(out, "# {}", query)?;
+                if let Some(entries) = entries {
+                    for (index, entry) in entries.iter().enumerate() {
+                        if let Some(name) = dictionary_header(entries, index) {
+                            writeln!(out, "== {} ==", name)?;
+                        }
+                        writeln!(out, "*{}*", &entry.key)?;
+                        for definition in &entry.definitions {
+                            write_definition(out, definition)?;
+                            writeln!(out)?;
+                        }
+                    }
+                } else {
+                    writeln!(out, "Not Found")?;
+                }
+            },
+            Some(Json) => {
+                write!(out, "{}", if self.count == 0 { "[" } else { "," })?;
+                serde_json::to_writer(&mut *out, &record)?;
+                writeln!(out)?;
+            },
+            Some(Jsonl) => {
+                serde_json::to_writer(&mut *out, &record)?;
+                writeln!(out)?;
+            },
+            Some(Markdown) => {
+                writeln!(out, "# {}", query)?;
+                writeln!(out)?;
+                if let Some(entries) = entries {
+                    write(out, Markdown, entries)?;
+                } else {
+                    writeln!(out, "_Not Found_")?;
+                    writeln!(out)?;
+                }
+            },
+            Some(Tsv) => {
+                // query, status, key, source, definition
+                let query = tsv_field(query);
+                if let Some(entries) = entries {
+                    let mut rows = vec![];
+                    write(&mut rows, Tsv, entries)?;
+                    for row in String::from_utf8_lossy(&rows).lines() {
+                        writeln!(out, "{}\tfound\t{}", query, row)?;
+                    }
+                } else {
+                    writeln!(out, "{}\tnot_found\t\t\t", query)?;
+                }
+            },
+        }
+
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> AppResultU {
+        if self.format == Some(Format::Json) {
+            writeln!(self.out, "{}", if self.count == 0 { "[]" } else { "]" })?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+
+pub fn write<W: Write>(out: &mut W, format: Format, entries: &[Entry]) -> AppResultU {
+    use self::Format::*;
+
+    match format {
+        Json => {
+            serde_json::to_writer(&mut *out, entries)?;
+            writeln!(out)?;
+        },
+        Jsonl => {
+            for entry in entries {
+                serde_json::to_writer(&mut *out, entry)?;
+                writeln!(out)?;
+            }
+        },
+        Markdown => {
+            for (index, entry) in entries.iter().enumerate() {
+                if let Some(name) = dictionary_header(entries, index) {
+                    writeln!(out, "**{}**", name)?;
+                    writeln!(out)?;
+                }
+                writeln!(out, "## {}", entry.key)?;
+                writeln!(out)?;
+                for definition in &entry.definitions {
+                    write!(out, "- ")?;
+                    if let Some(source) = &definition.source {
+                        write!(out, "`{}` ", source)?;
+                    }
+                    write_content(out, &definition.content)?;
+                    writeln!(out)?;
+                }
+                writeln!(out)?;
+            }
+        },
+        Tsv => {
+            for entry in entries {
+                for definition in &entry.definitions {
+                    let mut content = vec![];
+                    write_content(&mut content, &definition.content)?;
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}",
+                        tsv_field(&entry.key),
+                        tsv_field(definition.source.as_ref().map(String::as_str).unwrap_or("")),
+                        tsv_field(&String::from_utf8_lossy(&content)))?;
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+pub fn tsv_field(s: &str) -> String {
+    s.replace(|c: char| c == '\t' || c == '\n' || c == '\r', " ")
+}
+
+
+
+#[cfg(test)]#[test]
+fn test_tsv_field() {
+    assert_eq!(tsv_field("a\tb\nc"), "a b c");
+    assert_eq!(tsv_field("abc"), "abc");
+}
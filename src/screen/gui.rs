@@ -2,6 +2,7 @@
 use std::fmt::Write;
 use std::path::PathBuf;
 use std::process::exit;
+use std::sync::Arc;
 use std::sync::mpsc::{SyncSender, Receiver};
 use std::thread::{self, sleep};
 use std::time::Duration;
@@ -13,8 +14,11 @@ use gtk::prelude::*;
 use gtk::{CssProvider, ScrolledWindow, self, StyleContext};
 use structopt::StructOpt;
 
+use crate::config;
 use crate::delay::Delay;
-use crate::dictionary::{Definition, Dictionary, Entry, Text};
+use crate::dictionary::{dictionary_header, Definition, Dictionary, Entry};
+use crate::history::Source;
+use crate::theme::{self, Style, Theme};
 
 
 
@@ -33,7 +37,7 @@ pub struct Opt {
 }
 
 
-pub fn main(tx: SyncSender<Option<Vec<Entry>>>, rx: Receiver<Option<Vec<Entry>>>, opt: Opt, dictionary_path: PathBuf) {
+pub fn main(tx: SyncSender<Option<Vec<Entry>>>, rx: Receiver<Option<Vec<Entry>>>, opt: Opt, dictionary_paths: Vec<PathBuf>) {
     // Workaround - https://github.com/gtk-rs/gtk/issues/405#issuecomment-261809506
     // gtk::init().unwrap();
     unsafe {
@@ -76,16 +80,19 @@ pub fn main(tx: SyncSender<Option<Vec<Entry>>>, rx: Receiver<Option<Vec<Entry>>>
     let css_provider = CssProvider::new();
     StyleContext::add_provider_for_screen(&screen, &css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
 
-    css_provider.load_from_data("#label { background-color: #004040; }".as_bytes()).unwrap();
+    let theme = theme::current();
+    css_provider.load_from_data(format!("#label {{ {} }}", theme.base().css()).as_bytes()).unwrap();
 
     vbox.show();
     label.show();
     scroller.show();
     window.show();
 
-    connect_events(window, &scroller, entry, dictionary_path, tx);
+    connect_events(window, &scroller, entry, dictionary_paths, tx);
 
-    let font_size = opt.font_size.unwrap_or(13.0);
+    let config = &config::get().gui;
+    let font_size = opt.font_size.or(config.font_size).unwrap_or(13.0);
+    let font_name = opt.font_name.as_ref().or_else(|| config.font_name.as_ref());
 
     loop {
         while gtk::events_pending() {
@@ -95,11 +102,11 @@ pub fn main(tx: SyncSender<Option<Vec<Entry>>>, rx: Receiver<Option<Vec<Entry>>>
         for entries in rx.try_iter() {
             if let Some(entries) = entries {
                 let mut content = format!(r#"<span font="{}""#, font_size);
-                if let Some(font_name) = &opt.font_name {
-                    write!(content, r#"face="{}""#, font_name).unwrap();
+                if let Some(font_name) = font_name {
+                    write!(content, r#" face="{}""#, font_name).unwrap();
                 }
                 write!(content, ">").unwrap();
-                markup_entries(&mut content, &entries);
+                markup_entries(&mut content, &theme, &entries);
                 write!(content, "</span>").unwrap();
                 label.set_markup(&content);
             }
@@ -109,62 +116,54 @@ pub fn main(tx: SyncSender<Option<Vec<Entry>>>, rx: Receiver<Option<Vec<Entry>>>
     }
 }
 
-fn markup_entries(out: &mut String, entries: &[Entry]) {
-    for entry in entries {
-        color(out, &entry.key, "black", Some("yellow"), true);
+fn markup_entries(out: &mut String, theme: &Theme, entries: &[Entry]) {
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(name) = dictionary_header(entries, index) {
+            color(out, &theme.dictionary, &format!(" {} ", name));
+            writeln!(out).unwrap();
+        }
+        color(out, &theme.key, &entry.key);
         writeln!(out).unwrap();
 
         for definition in &entry.definitions {
-            markup_definition(out, definition);
+            markup_definition(out, theme, definition);
             writeln!(out).unwrap();
         }
     }
 }
 
-fn markup_definition(out: &mut String, definition: &Definition) {
+fn markup_definition(out: &mut String, theme: &Theme, definition: &Definition) {
+    if let Some(source) = &definition.source {
+        color(out, &theme.source, source);
+        write!(out, " ").unwrap();
+    }
     for (index, text) in definition.content.iter().enumerate() {
         if 0 < index {
             write!(out, " ").unwrap();
         }
-        markup_text(out, text);
+        for (style, s) in theme.parts(text) {
+            color(out, style, &s);
+        }
     }
 }
 
-fn markup_text(out: &mut String, text: &Text) {
-    use self::Text::*;
-
-    match &text {
-        Annot(s) => color(out, s, "yellow", None, false),
-        Countability(c) => color(out, &c.to_string(), "yellow", None, false),
-        Class(s) => color(out, s, "lightblue", None, false),
-        Definition(s) => color(out, s, "white", None, true),
-        Error(s) => color(out, s, "red", None, true),
-        Etymology(s) => {
-            color(out, "語源 ", "magenta", None, true);
-            color(out, s, "white", None, false);
-        },
-        Example(s) => color(out, s, "lightgreen", None, false),
-        Information(s) => color(out, s, "cyan", None, false),
-        Note(s) => color(out, s, "white", None, false),
-        Tag(s) => color(out, s, "orangered", None, false),
-        Word(s) => color(out, s, "black", Some("yellow"), false),
+fn color(out: &mut String, style: &Style, s: &str) {
+    write!(out, "<span").unwrap();
+    if let Some(fg) = style.fg {
+        write!(out, r#" foreground="{}""#, fg.css()).unwrap();
     }
-
-}
-
-fn color(out: &mut String, s: &str, fg: &str, bg: Option<&str>, bold: bool) {
-    write!(out, r#"<span foreground="{}""#, fg).unwrap();
-    if let Some(bg) = bg {
-        write!(out, r#" background="{}""#, bg).unwrap();
+    if let Some(bg) = style.bg {
+        write!(out, r#" background="{}""#, bg.css()).unwrap();
     }
-    if bold {
+    if style.bold {
         write!(out, r#" weight="bold""#).unwrap();
     }
     write!(out, r#">{}</span>"#, markup_escape_text(s)).unwrap();
 }
 
-fn connect_events(window: gtk::Window, scroller: &gtk::ScrolledWindow, entry: gtk::Entry, dictionary_path: PathBuf, tx: SyncSender<Option<Vec<Entry>>>) {
+fn connect_events(window: gtk::Window, scroller: &gtk::ScrolledWindow, entry: gtk::Entry, dictionary_paths: Vec<PathBuf>, tx: SyncSender<Option<Vec<Entry>>>) {
     let delay = Delay::new(Duration::from_millis(250));
+    let dictionary = Arc::new(Dictionary::new(&dictionary_paths));
 
     window.connect_delete_event(|_, _| {
         exit(0);
@@ -193,9 +192,13 @@ fn connect_events(window: gtk::Window, scroller: &gtk::ScrolledWindow, entry: gt
             if query.is_empty() {
                 return Inhibit(false);
             }
-            if let Ok(entries) = Dictionary::get_word(&dictionary_path, &query) {
-                thread::spawn(clone_army!([tx, delay] move || {
+            if let Ok(entries) = dictionary.get_smart(&query) {
+                let query = query.to_string();
+                thread::spawn(clone_army!([tx, delay, dictionary] move || {
                     if delay.wait() {
+                        if let Err(error) = dictionary.record_history(&query, Source::Gui, entries.is_some()) {
+                            eprintln!("Failed to record history: {}", error);
+                        }
                         tx.send(entries).unwrap()
                     }
                 }));
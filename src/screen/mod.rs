@@ -8,10 +8,12 @@ use structopt::clap::AppSettings;
 
 mod curses;
 pub mod color;
+pub mod format;
 pub mod gui;
 pub mod plain;
 
 use crate::dictionary::Entry;
+use crate::errors::{AppError, AppResult};
 
 
 
@@ -33,8 +35,15 @@ pub struct Screen {
     tx: SyncSender<Option<Vec<Entry>>>,
 }
 
+impl Opt {
+    /// e.g. "color", "gui"
+    pub fn from_name(name: &str) -> AppResult<Self> {
+        Opt::from_iter_safe(&["screen", name]).map_err(|it| AppError::Standard(it.message))
+    }
+}
+
 impl Screen {
-    pub fn new(opt: Opt, dictionary_path: PathBuf, bind_to: String) -> Self {
+    pub fn new(opt: Opt, dictionary_paths: Vec<PathBuf>, bind_to: String) -> Self {
         use self::Opt::*;
 
         let (tx, rx) = sync_channel(0);
@@ -47,7 +56,7 @@ impl Screen {
             Color =>
                 color::main(rx).unwrap(),
             Gui(opt) =>
-                gui::main(tx, rx, opt, dictionary_path),
+                gui::main(tx, rx, opt, dictionary_paths),
             Plain =>
                 plain::main(rx).unwrap(),
         });
@@ -2,7 +2,7 @@
 use std::sync::mpsc::Receiver;
 use std::io::{BufWriter, Error as IOError, stdout, Write};
 
-use crate::dictionary::{Entry, Text};
+use crate::dictionary::{dictionary_header, Definition, Entry, Text};
 use crate::errors::AppResultU;
 
 
@@ -19,6 +19,37 @@ pub fn main(rx: Receiver<Option<Vec<Entry>>>) -> AppResultU {
 }
 
 pub fn print(entries: Vec<Entry>) -> AppResultU {
+    let out = stdout();
+    let out = out.lock();
+    let mut out = BufWriter::new(out);
+
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(name) = dictionary_header(&entries, index) {
+            writeln!(out, "== {} ==", name)?;
+        }
+        writeln!(out, "*{}*", &entry.key)?;
+        for definition in &entry.definitions {
+            write_definition(&mut out, definition)?;
+            writeln!(out)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_not_found() {
+    println!("Not Found");
+}
+
+pub fn write_definition<W: Write>(out: &mut W, definition: &Definition) -> Result<(), IOError> {
+    if let Some(source) = &definition.source {
+        write!(out, "[{}] ", source)?;
+    }
+
+    write_content(out, &definition.content)
+}
+
+pub fn write_content<W: Write>(out: &mut W, content: &[Text]) -> Result<(), IOError> {
     fn color<W: Write>(out: &mut W, text: &Text) -> Result<(), IOError> {
         use self::Text::*;
 
@@ -34,26 +65,12 @@ pub fn print(entries: Vec<Entry>) -> AppResultU {
         }
     }
 
-    let out = stdout();
-    let out = out.lock();
-    let mut out = BufWriter::new(out);
-
-    for entry in entries {
-        writeln!(out, "*{}*", &entry.key)?;
-        for definition in &entry.definitions {
-            for (index, text) in definition.content.iter().enumerate() {
-                if 0 < index {
-                    write!(out, " ")?;
-                }
-                color(&mut out, text)?;
-            }
-            writeln!(out)?;
+    for (index, text) in content.iter().enumerate() {
+        if 0 < index {
+            write!(out, " ")?;
         }
+        color(out, text)?;
     }
 
     Ok(())
 }
-
-pub fn print_not_found() {
-    println!("Not Found");
-}
@@ -26,6 +26,35 @@ pub fn fix_word(s: &str) -> Option<String> {
     }
 }
 
+/// Split a Japanese definition into the fragments for reverse lookup
+pub fn fragments(s: &str) -> Vec<String> {
+    s.split(|c| c == '、' || c == '；')
+        .filter_map(normalize_fragment)
+        .collect()
+}
+
+pub fn normalize_fragment(s: &str) -> Option<String> {
+    let s = wide2ascii(s);
+    let mut result = "".to_owned();
+    let mut depth = 0;
+
+    for c in s.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' if 0 < depth => depth -= 1,
+            c if depth == 0 => result.push(c),
+            _ => (),
+        }
+    }
+
+    let result = result.trim();
+    if result.is_empty() {
+        None
+    } else {
+        Some(result.to_owned())
+    }
+}
+
 pub fn scan_words(word_type: WordType, s: &str) -> Vec<String> {
     let mut result = vec![];
     let mut in_word = false;
@@ -147,6 +176,14 @@ fn test_scan_words() {
     // 【変化】複 wildcats、【分節】wild・cat
 }
 
+#[cfg(test)]#[test]
+fn test_fragments() {
+    assert_eq!(fragments("猫、ネコ；（動物の）キャット"), vec!["猫", "ネコ", "キャット"]);
+    assert_eq!(fragments("（～を）ひっかく、"), vec!["ひっかく"]);
+    assert_eq!(fragments("ＡＢＣ順"), vec!["ABC順"]);
+    assert_eq!(fragments("、（注）"), Vec::<String>::new());
+}
+
 #[cfg(test)]#[test]
 fn test_patterns() {
     fn ps(s: &str) -> Vec<String> {
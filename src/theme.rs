@@ -0,0 +1,388 @@
+
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+
+use crate::config;
+use crate::dictionary::Text;
+
+
+
+pub const DEFAULT_THEME: &str = "dark";
+
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    /// Text put before the content (e.g. "語源 ")
+    pub prefix: Option<String>,
+    /// Style of the prefix (Same as the content if omitted)
+    pub prefix_style: Option<Box<Style>>,
+}
+
+/// Missing fields of user themes are taken from the dark theme (Field by field of each style)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(from = "ThemePatch")]
+pub struct Theme {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    /// Header of each dictionary (`-d a,b`)
+    pub dictionary: Style,
+    pub key: Style,
+    pub source: Style,
+    pub not_found: Style,
+    pub annot: Style,
+    pub class: Style,
+    pub countability: Style,
+    pub definition: Style,
+    pub error: Style,
+    pub etymology: Style,
+    pub example: Style,
+    pub information: Style,
+    pub note: Style,
+    pub tag: Style,
+    pub word: Style,
+}
+
+/// User theme as written in the config
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ThemePatch {
+    foreground: Option<Color>,
+    background: Option<Color>,
+    #[serde(flatten)]
+    styles: HashMap<String, StylePatch>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StylePatch {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: Option<bool>,
+    prefix: Option<String>,
+    prefix_style: Option<Box<StylePatch>>,
+}
+
+
+
+/// The theme named in the config
+pub fn current() -> Theme {
+    let config = config::get();
+    let name = config.theme.as_ref().map(String::as_str).unwrap_or(DEFAULT_THEME);
+
+    if let Some(theme) = config.themes.get(name) {
+        return theme.clone();
+    }
+
+    match name {
+        "dark" => Theme::dark(),
+        "light" => Theme::light(),
+        _ => {
+            eprintln!("Unknown theme: {}", name);
+            Theme::dark()
+        }
+    }
+}
+
+
+impl Color {
+    pub fn ansi_fg(self) -> u8 {
+        30 + self as u8
+    }
+
+    pub fn ansi_bg(self) -> u8 {
+        40 + self as u8
+    }
+
+    /// For GTK and HTML (Readable on both of the dark and light backgrounds)
+    pub fn css(self) -> &'static str {
+        use self::Color::*;
+
+        match self {
+            Black => "#000000",
+            Red => "#e0402a",
+            Green => "#3aaa3a",
+            Yellow => "#d8b000",
+            Blue => "#4a8ae0",
+            Magenta => "#c04ac0",
+            Cyan => "#2aaab0",
+            White => "#f8f8f8",
+        }
+    }
+}
+
+impl Style {
+    fn new(fg: Option<Color>, bg: Option<Color>, bold: bool) -> Self {
+        Style { fg, bg, bold, prefix: None, prefix_style: None }
+    }
+
+    fn with_prefix(mut self, prefix: &str, style: Style) -> Self {
+        self.prefix = Some(prefix.to_owned());
+        self.prefix_style = Some(Box::new(style));
+        self
+    }
+
+    /// CSS declarations
+    pub fn css(&self) -> String {
+        let mut result = vec![];
+        if let Some(fg) = self.fg {
+            result.push(format!("color: {};", fg.css()));
+        }
+        if let Some(bg) = self.bg {
+            result.push(format!("background-color: {};", bg.css()));
+        }
+        if self.bold {
+            result.push("font-weight: bold;".to_owned());
+        }
+        result.join(" ")
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        use self::Color::*;
+
+        Theme {
+            foreground: Some(White),
+            background: Some(Black),
+            dictionary: Style::new(Some(White), Some(Blue), true),
+            key: Style::new(Some(Black), Some(Yellow), true),
+            source: Style::new(Some(Black), Some(White), false),
+            not_found: Style::new(Some(White), Some(Red), true),
+            annot: Style::new(Some(Yellow), None, false),
+            class: Style::new(Some(Blue), None, false),
+            countability: Style::new(Some(Yellow), None, true),
+            definition: Style::new(Some(White), None, true),
+            error: Style::new(Some(Red), None, true),
+            etymology: Style::default().with_prefix("語源 ", Style::new(Some(Magenta), None, true)),
+            example: Style::new(Some(Green), None, false),
+            information: Style::new(Some(Cyan), None, false),
+            note: Style::default(),
+            tag: Style::new(Some(Red), None, true),
+            word: Style::new(Some(Black), Some(Yellow), false),
+        }
+    }
+
+    pub fn light() -> Self {
+        use self::Color::*;
+
+        Theme {
+            foreground: Some(Black),
+            background: Some(White),
+            dictionary: Style::new(Some(White), Some(Blue), true),
+            key: Style::new(Some(Black), Some(Yellow), true),
+            source: Style::new(Some(White), Some(Black), false),
+            not_found: Style::new(Some(White), Some(Red), true),
+            annot: Style::new(Some(Magenta), None, false),
+            class: Style::new(Some(Blue), None, false),
+            countability: Style::new(Some(Magenta), None, true),
+            definition: Style::new(Some(Black), None, true),
+            error: Style::new(Some(Red), None, true),
+            etymology: Style::default().with_prefix("語源 ", Style::new(Some(Magenta), None, true)),
+            example: Style::new(Some(Green), None, false),
+            information: Style::new(Some(Cyan), None, false),
+            note: Style::default(),
+            tag: Style::new(Some(Red), None, true),
+            word: Style::new(Some(Black), Some(Yellow), false),
+        }
+    }
+
+    /// Foreground and background
+    pub fn base(&self) -> Style {
+        Style::new(self.foreground, self.background, false)
+    }
+
+    fn style_by_name(&mut self, name: &str) -> Option<&mut Style> {
+        let style = match name {
+            "dictionary" => &mut self.dictionary,
+            "key" => &mut self.key,
+            "source" => &mut self.source,
+            "not_found" => &mut self.not_found,
+            "annot" => &mut self.annot,
+            "class" => &mut self.class,
+            "countability" => &mut self.countability,
+            "definition" => &mut self.definition,
+            "error" => &mut self.error,
+            "etymology" => &mut self.etymology,
+            "example" => &mut self.example,
+            "information" => &mut self.information,
+            "note" => &mut self.note,
+            "tag" => &mut self.tag,
+            "word" => &mut self.word,
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    pub fn style(&self, text: &Text) -> &Style {
+        use self::Text::*;
+
+        match text {
+            Annot(_) => &self.annot,
+            Class(_) => &self.class,
+            Countability(_) => &self.countability,
+            Definition(_) => &self.definition,
+            Error(_) => &self.error,
+            Etymology(_) => &self.etymology,
+            Example(_) => &self.example,
+            Information(_) => &self.information,
+            Note(_) => &self.note,
+            Tag(_) => &self.tag,
+            Word(_) => &self.word,
+        }
+    }
+
+    /// Stylesheet for the HTML made by `command::html`
+    pub fn css(&self) -> String {
+        let mut result = String::new();
+        let body = self.base();
+        let rules = [
+            ("body", &body),
+            (".eitaro-dictionary", &self.dictionary),
+            (".eitaro-term", &self.key),
+            (".eitaro-def-source", &self.source),
+            (".eitaro-def-annotation", &self.annot),
+            (".eitaro-def-class", &self.class),
+            (".eitaro-def-countability", &self.countability),
+            (".eitaro-def-definition", &self.definition),
+            (".eitaro-def-error", &self.error),
+            (".eitaro-def-etymology", &self.etymology),
+            (".eitaro-def-example", &self.example),
+            (".eitaro-def-information", &self.information),
+            (".eitaro-def-note", &self.note),
+            (".eitaro-def-tag", &self.tag),
+            (".eitaro-def-key", &self.word),
+        ];
+        for (selector, style) in rules.iter() {
+            result.push_str(&format!("{} {{ {} }}\n", selector, style.css()));
+            if let Some(prefix) = &style.prefix {
+                let mut declarations = format!("content: \"{}\";", prefix.replace('\\', "\\\\").replace('"', "\\\""));
+                if let Some(prefix_style) = &style.prefix_style {
+                    declarations.push(' ');
+                    declarations.push_str(&prefix_style.css());
+                }
+                result.push_str(&format!("{}::before {{ {} }}\n", selector, declarations.trim_end()));
+            }
+        }
+        result
+    }
+
+    /// The prefix and the content with their styles
+    pub fn parts(&self, text: &Text) -> Vec<(&Style, String)> {
+        let content = match text {
+            Text::Countability(c) => c.to_string(),
+            Text::Annot(s) | Text::Class(s) | Text::Definition(s) | Text::Error(s) | Text::Etymology(s) | Text::Example(s) |
+                Text::Information(s) | Text::Note(s) | Text::Tag(s) | Text::Word(s) => s.to_owned(),
+        };
+        let style = self.style(text);
+        let mut result = vec![];
+        if let Some(prefix) = &style.prefix {
+            let prefix_style = style.prefix_style.as_ref().map(|it| &**it).unwrap_or(style);
+            result.push((prefix_style, prefix.to_owned()));
+        }
+        result.push((style, content));
+        result
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl From<ThemePatch> for Theme {
+    fn from(patch: ThemePatch) -> Self {
+        let mut theme = Theme::dark();
+        if patch.foreground.is_some() {
+            theme.foreground = patch.foreground;
+        }
+        if patch.background.is_some() {
+            theme.background = patch.background;
+        }
+        for (name, style) in patch.styles {
+            match theme.style_by_name(&name) {
+                Some(base) => style.apply(base),
+                None => eprintln!("Unknown theme item: {}", name),
+            }
+        }
+        theme
+    }
+}
+
+impl StylePatch {
+    fn apply(self, style: &mut Style) {
+        if self.fg.is_some() {
+            style.fg = self.fg;
+        }
+        if self.bg.is_some() {
+            style.bg = self.bg;
+        }
+        if let Some(bold) = self.bold {
+            style.bold = bold;
+        }
+        if self.prefix.is_some() {
+            style.prefix = self.prefix;
+        }
+        if let Some(patch) = self.prefix_style {
+            let mut prefix_style = style.prefix_style.take().map(|it| *it).unwrap_or_default();
+            patch.apply(&mut prefix_style);
+            style.prefix_style = Some(Box::new(prefix_style));
+        }
+    }
+}
+
+
+#[cfg(test)]#[test]
+fn test_style_css() {
+    assert_eq!(Style::new(Some(Color::Red), None, true).css(), "color: #e0402a; font-weight: bold;");
+    assert_eq!(Style::default().css(), "");
+    assert_eq!(Color::Red.ansi_fg(), 31);
+    assert_eq!(Color::White.ansi_bg(), 47);
+}
+
+#[cfg(test)]#[test]
+fn test_parts() {
+    let theme = Theme::dark();
+    let parts = theme.parts(&Text::Etymology("cat".to_owned()));
+    let parts: Vec<(Option<Color>, bool, &str)> = parts.iter().map(|(style, s)| (style.fg, style.bold, s.as_str())).collect();
+    assert_eq!(parts, vec![(Some(Color::Magenta), true, "語源 "), (None, false, "cat")]);
+
+    let parts = theme.parts(&Text::Example("meow".to_owned()));
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0].1, "meow");
+}
+
+#[cfg(test)]#[test]
+fn test_patch() {
+    let theme: Theme = toml::from_str(r#"
+        foreground = "black"
+        etymology = { fg = "red" }
+        key = { bold = false }
+    "#).unwrap();
+
+    assert_eq!(theme.foreground, Some(Color::Black));
+    assert_eq!(theme.background, Some(Color::Black));
+    assert_eq!(theme.etymology.fg, Some(Color::Red));
+    assert_eq!(theme.etymology.prefix, Some("語源 ".to_owned()));
+    assert_eq!(theme.etymology.prefix_style.map(|it| it.fg), Some(Some(Color::Magenta)));
+    assert_eq!(theme.key.fg, Some(Color::Black));
+    assert_eq!(theme.key.bg, Some(Color::Yellow));
+    assert!(!theme.key.bold);
+}
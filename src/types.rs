@@ -1,11 +1,54 @@
 
+use std::str::FromStr;
 
+use crate::errors::AppError;
 
-#[derive(Clone, Copy, Debug)]
+
+
+pub const DICTIONARY_FORMAT_NAMES: &[&str] = &["csv", "eijiro", "ejdic", "gene", "json", "tsv"];
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DictionaryFormat {
     Csv,
     Eijiro,
     Ejdic,
     Gene,
     JsonSimpleKeyValue,
+    /// CSV separated by tabs
+    Tsv,
+}
+
+
+impl DictionaryFormat {
+    pub fn name(self) -> &'static str {
+        use self::DictionaryFormat::*;
+
+        match self {
+            Csv => "csv",
+            Eijiro => "eijiro",
+            Ejdic => "ejdic",
+            Gene => "gene",
+            JsonSimpleKeyValue => "json",
+            Tsv => "tsv",
+        }
+    }
+}
+
+impl FromStr for DictionaryFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use self::DictionaryFormat::*;
+
+        match s {
+            "csv" => Ok(Csv),
+            "eijiro" => Ok(Eijiro),
+            "ejdic" => Ok(Ejdic),
+            "gene" => Ok(Gene),
+            "json" => Ok(JsonSimpleKeyValue),
+            "tsv" => Ok(Tsv),
+            _ => Err(AppError::Eitaro("Unknown dictionary format")),
+        }
+    }
 }
@@ -0,0 +1,108 @@
+
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, File};
+use std::io::{BufReader, BufWriter};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::dictionary::Definition;
+use crate::errors::{AppError, AppResult, AppResultU};
+use crate::parser::eijiro::parse_line;
+use crate::path::get_user_dictionary_path;
+
+
+
+pub const SOURCE_NAME: &str = "user";
+
+
+/// User defined entries in a JSON file apart from the built database (survives `eitaro build`)
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UserDictionary {
+    /// By the lowercased word
+    entries: BTreeMap<String, UserEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserEntry {
+    pub key: String,
+    /// Eijiro notation
+    pub definitions: Vec<String>,
+    /// Hide the definitions in the built database
+    #[serde(default)]
+    pub replace: bool,
+}
+
+
+impl UserDictionary {
+    pub fn load() -> AppResult<Self> {
+        let path = get_user_dictionary_path()?;
+        if !path.exists() {
+            return Ok(UserDictionary::default());
+        }
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn save(&self) -> AppResultU {
+        let path = get_user_dictionary_path()?;
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, word: &str, definition: &str, replace: bool) -> AppResultU {
+        validate(definition)?;
+        let entry = self.entries.entry(word.to_lowercase()).or_insert_with(|| UserEntry {
+            key: word.to_owned(),
+            definitions: vec![],
+            replace,
+        });
+        entry.definitions.push(definition.to_owned());
+        entry.replace |= replace;
+        Ok(())
+    }
+
+    pub fn get(&self, word: &str) -> Option<&UserEntry> {
+        self.entries.get(&word.to_lowercase())
+    }
+
+    pub fn remove(&mut self, word: &str) -> bool {
+        self.entries.remove(&word.to_lowercase()).is_some()
+    }
+
+    pub fn set(&mut self, word: &str, definitions: Vec<String>, replace: bool) -> AppResultU {
+        for definition in &definitions {
+            validate(definition)?;
+        }
+        let entry = UserEntry { key: word.to_owned(), definitions, replace };
+        self.entries.insert(word.to_lowercase(), entry);
+        Ok(())
+    }
+}
+
+
+impl UserEntry {
+    pub fn to_definitions(&self) -> AppResult<Vec<Definition>> {
+        let mut result = vec![];
+        for definition in &self.definitions {
+            result.push(Definition {
+                key: self.key.clone(),
+                content: parse_line(definition)?,
+                source: Some(SOURCE_NAME.to_owned()),
+            });
+        }
+        Ok(result)
+    }
+}
+
+
+fn validate(definition: &str) -> AppResultU {
+    if definition.trim().is_empty() {
+        return Err(AppError::Eitaro("Empty definition"));
+    }
+    parse_line(definition)?;
+    Ok(())
+}